@@ -1,5 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer, Mint};
+use anchor_lang::solana_program::keccak;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 declare_id!("8HVDihB8NrYpqzRNrFuiSPUC7B4rRqa8HFRJNWoxH4JK");
 
@@ -7,14 +12,16 @@ declare_id!("8HVDihB8NrYpqzRNrFuiSPUC7B4rRqa8HFRJNWoxH4JK");
 pub mod staker {
     use super::*;
 
-    // REPLACE ADDRESS of RR3 mint by running solana address -k keys_for_test/rr3_mint_localhost.json
-    pub const RR3_MINT_ADDRESS: &str = "Enpkrq4rZwT8mCjk6fmqaH9pLU3iwrDzf1772Ct1eLqx";
+    // Each staked mint gets its own Pool PDA ([b"pool", mint]) created by initialize_pool;
+    // all state, vaults and stake records are namespaced by the pool
     
     // New 3.33% Commission Structure (basis points)
     pub const TOTAL_COMMISSION_BPS: u64 = 333; // 3.33% total commission on staking (taken from staked RR3)
     pub const FEE_WALLET_BPS: u64 = 300; // 3.00% fee wallet
     pub const EXPENSE_WALLET_BPS: u64 = 33; // 0.33% expense wallet
     pub const MARKETING_WALLET_BPS: u64 = 3; // 0.03% marketing wallet
+    // The 3.33% commission is split between the three wallets pro rata to the weights above
+    pub const COMMISSION_SPLIT_TOTAL_BPS: u64 = FEE_WALLET_BPS + EXPENSE_WALLET_BPS + MARKETING_WALLET_BPS;
     // Note: Burn wallet commission removed (was 0.003% - too small for basis points)
     
     // Rewards are distributed in RR3 tokens (not SOL)
@@ -28,307 +35,966 @@ pub mod staker {
     pub const LOCK_2_YEARS: i64 = 2 * 365 * 24 * 60 * 60; // 2 years in seconds (63,072,000)
     pub const LOCK_3_YEARS: i64 = 3 * 365 * 24 * 60 * 60; // 3 years in seconds (94,608,000)
 
-    // Final reward multipliers (basis points). These determine total payout as:
-    // final_payout = principal * multiplier / 100
-    // e.g. 105 => 5% reward on top of principal
-    pub const REWARD_MULTIPLIER_3_MONTHS: u64 = 105; // 5% total payout
-    pub const REWARD_MULTIPLIER_6_MONTHS: u64 = 112; // 12% total payout
-    pub const REWARD_MULTIPLIER_1_YEAR: u64 = 130;   // 30% total payout
-    pub const REWARD_MULTIPLIER_2_YEARS: u64 = 170;  // 70% total payout
-    pub const REWARD_MULTIPLIER_3_YEARS: u64 = 250;  // 150% total payout
-
+    // Non-tier lock durations accepted by dev-lock-periods builds (for testing only)
+    #[cfg(feature = "dev-lock-periods")]
+    pub const DEV_MIN_LOCK_DURATION: i64 = 5 * 60; // 5 minutes
+    #[cfg(feature = "dev-lock-periods")]
+    pub const DEV_FULL_WEIGHT_LOCK_DURATION: i64 = 30 * 24 * 60 * 60; // Shorter dev locks get 0.5x monthly weight
+
+    pub const BPS_DENOMINATOR: u64 = 10_000; // 10,000 bps = 1.0x
+
+    // Default lock tier table written by initialize_staking_config:
+    // (lock duration, monthly reward weight bps, completion multiplier bps)
+    // Monthly weight determines the share of each monthly reward pool, e.g. 10_100 => 1.01x
+    // Completion multiplier determines final payout = principal * multiplier / 10_000, e.g. 10_500 => 5% bonus
+    pub const DEFAULT_LOCK_TIERS: [(i64, u64, u64); 5] = [
+        (LOCK_3_MONTHS, 10_000, 10_500), // 1.0000x monthly, 5% completion bonus
+        (LOCK_6_MONTHS, 10_100, 11_200), // 1.0100x monthly, 12% completion bonus
+        (LOCK_1_YEAR, 10_200, 13_000),   // 1.0200x monthly, 30% completion bonus
+        (LOCK_2_YEARS, 10_300, 17_000),  // 1.0300x monthly, 70% completion bonus
+        (LOCK_3_YEARS, 10_333, 25_000),  // 1.0333x monthly, 150% completion bonus
+    ];
+    pub const MAX_LOCK_TIERS: usize = 10;
+
+    // Emergency buyback pays principal * (10_000 - discount) / 10_000 from the buyback treasury
+    // Buyback discount of new pools when PoolConfig.buyback_discount_bps is None
+    pub const DEFAULT_BUYBACK_DISCOUNT_BPS: u64 = 1_000; // 10% below principal
+
+    // Pause flags (bits of DistributionState.paused)
+    pub const PAUSE_STAKING: u8 = 1 << 0;      // stake, add_stake
+    pub const PAUSE_CLAIMING: u8 = 1 << 1;     // claim_rr3_rewards
+    pub const PAUSE_UNSTAKING: u8 = 1 << 2;    // unstake (emergency_withdraw is never paused)
+    pub const PAUSE_DISTRIBUTION: u8 = 1 << 3; // rounds, reward settlement and commission burns
+    pub const PAUSE_ALL: u8 = PAUSE_STAKING | PAUSE_CLAIMING | PAUSE_UNSTAKING | PAUSE_DISTRIBUTION;
+
+    // Maximum stake records credited by one crank_rewards call (keeps it within compute limits)
+    pub const MAX_CRANK_STAKES: usize = 10;
+
+    // Largest Merkle reward tree; its claimed bitmap must fit in one account created by CPI (10 KiB)
+    pub const MAX_MERKLE_LEAVES: u32 = 64_000;
+
+    // Fixed-point scale for acc_reward_per_weighted_share
+    pub const ACC_REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+    // Minimum time between distribution rounds: new pools start at the default, set_round_interval
+    // refuses anything below the floor. The devnet-timings feature enables short test windows
+    #[cfg(not(feature = "devnet-timings"))]
+    pub const DEFAULT_ROUND_INTERVAL: i64 = 30 * 24 * 60 * 60; // 30 days
+    #[cfg(not(feature = "devnet-timings"))]
+    pub const MIN_ROUND_INTERVAL: i64 = 28 * 24 * 60 * 60;     // 28 days
+    #[cfg(feature = "devnet-timings")]
+    pub const DEFAULT_ROUND_INTERVAL: i64 = 5 * 60;            // 5 minutes
+    #[cfg(feature = "devnet-timings")]
+    pub const MIN_ROUND_INTERVAL: i64 = 60;                    // 1 minute
+
+
+    // Create a staking pool for a mint with its distribution state and default lock tiers (upgrade authority only)
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        mint: Pubkey,
+        config: PoolConfig,
+    ) -> Result<()> {
+        require!(
+            program_upgrade_authority(&ctx.accounts.program_data)? == Some(ctx.accounts.admin.key()),
+            StakeError::NotUpgradeAuthority
+        );
 
+        let buyback_discount_bps = config.buyback_discount_bps.unwrap_or(DEFAULT_BUYBACK_DISCOUNT_BPS);
+        require!(buyback_discount_bps <= BPS_DENOMINATOR, StakeError::InvalidBuybackDiscount);
 
-    // Initialize global distribution state (admin only)
-    pub fn initialize_distribution_state(
-        _ctx: Context<InitializeDistributionState>
-    ) -> Result<()> {
-        let distribution_state = &mut _ctx.accounts.distribution_state;
+        let pool = &mut ctx.accounts.pool;
+        let distribution_state = &mut ctx.accounts.distribution_state;
+        let staking_config = &mut ctx.accounts.staking_config;
         let clock = Clock::get()?;
-        
-        distribution_state.admin = _ctx.accounts.admin.key();
+
+        // Decimals are read from the mint once; the minimum stake is configured in whole tokens
+        pool.mint = mint;
+        pool.decimals = ctx.accounts.rr3_mint.decimals;
+        pool.min_stake_amount = whole_tokens_to_raw(config.min_stake_tokens, pool.decimals)?;
+        pool.created_at = clock.unix_timestamp;
+        pool.bump = ctx.bumps.pool;
+        pool.principal_vault_bump = 0;
+        pool.rewards_vault_bump = 0;
+        pool.bonus_reserve_bump = 0;
+
+        distribution_state.admin = ctx.accounts.admin.key();
         distribution_state.last_distribution_time = clock.unix_timestamp;
         distribution_state.total_rr3_staked = 0;
+        distribution_state.acc_reward_per_weighted_share = 0;
+        distribution_state.total_bonus_liability = 0;
         distribution_state.monthly_rr3_for_rewards = 0;
-        distribution_state.monthly_expense_fees = 0;
-        distribution_state.monthly_marketing_fees = 0;
-        distribution_state.monthly_burn_fees = 0;
+        distribution_state.total_fee_commission = 0;
+        distribution_state.total_expense_commission = 0;
+        distribution_state.total_marketing_commission = 0;
         distribution_state.distribution_round = 0;
-        
-        msg!("Distribution state initialized - rewards will be distributed in RR3 tokens");
+        distribution_state.fee_wallet = Pubkey::default();
+        distribution_state.expense_wallet = Pubkey::default();
+        distribution_state.marketing_wallet = Pubkey::default();
+
+        // Roles come from the pool config and can be changed later via set_roles
+        distribution_state.pending_admin = Pubkey::default();
+        distribution_state.distributor = config.distributor;
+        distribution_state.treasurer = config.treasurer;
+        distribution_state.pauser = config.pauser;
+        distribution_state.paused = 0;
+        distribution_state.total_buyback_principal = 0;
+        distribution_state.total_buyback_paid = 0;
+        distribution_state.total_user_burned = 0;
+        distribution_state.reward_carryover = 0;
+        distribution_state.carryover_sink = Pubkey::default();
+        distribution_state.total_principal = 0;
+        distribution_state.reward_liability = 0;
+        distribution_state.total_commission_burned = 0;
+
+        staking_config.buyback_discount_bps = buyback_discount_bps;
+        staking_config.keeper_tip_per_stake = 0;
+        staking_config.round_interval = DEFAULT_ROUND_INTERVAL;
+        staking_config.tiers = DEFAULT_LOCK_TIERS
+            .iter()
+            .map(|&(lock_duration, monthly_weight_bps, completion_multiplier_bps)| LockTier {
+                lock_duration,
+                monthly_weight_bps,
+                completion_multiplier_bps,
+                enabled: true,
+            })
+            .collect();
+
+        emit!(PoolInitialized {
+            pool: pool.key(),
+            mint,
+            decimals: pool.decimals,
+            min_stake_amount: pool.min_stake_amount,
+            admin: distribution_state.admin,
+            timestamp: clock.unix_timestamp,
+        });
+        for tier in staking_config.tiers.iter() {
+            emit!(LockTierChanged {
+                pool: pool.key(),
+                lock_duration: tier.lock_duration,
+                monthly_weight_bps: tier.monthly_weight_bps,
+                completion_multiplier_bps: tier.completion_multiplier_bps,
+                enabled: tier.enabled,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        msg!("Pool initialized for mint {} with {} lock tiers, minimum stake {} tokens",
+            mint,
+            staking_config.tiers.len(),
+            format_ui_amount(pool.min_stake_amount, pool.decimals)
+        );
         Ok(())
     }
 
-    // Admin function: Record monthly RR3 allocation for reward distribution
-    pub fn record_monthly_collection(
-        ctx: Context<RecordMonthlyCollection>,
-        total_rr3_for_rewards: u64, // Total RR3 tokens allocated for rewards (in RR3 token units)
+    // Admin function: Propose a new admin (takes effect once the new admin calls accept_admin)
+    pub fn propose_admin(
+        ctx: Context<ProposeAdmin>,
+        new_admin: Pubkey,
     ) -> Result<()> {
         let distribution_state = &mut ctx.accounts.distribution_state;
-        let clock = Clock::get()?;
-        
-        // Check if it's been at least 5 minutes since last distribution
-        let time_since_last = clock.unix_timestamp - distribution_state.last_distribution_time;
-        require!(
-            time_since_last >= (5 * 60) as i64, // 5 minutes instead of 30 days
-            StakeError::TooEarlyForDistribution
-        );
 
-        // Calculate commission breakdown from RR3 allocated (3.33% total of staked amount stays as commission)
-        let fee_wallet_amount = (total_rr3_for_rewards * FEE_WALLET_BPS) / TOTAL_COMMISSION_BPS;
-        let expense_wallet_amount = (total_rr3_for_rewards * EXPENSE_WALLET_BPS) / TOTAL_COMMISSION_BPS;
-        let marketing_wallet_amount = (total_rr3_for_rewards * MARKETING_WALLET_BPS) / TOTAL_COMMISSION_BPS;
-        let burn_wallet_amount = total_rr3_for_rewards.saturating_sub(fee_wallet_amount + expense_wallet_amount + marketing_wallet_amount);
-
-        distribution_state.monthly_rr3_for_rewards = fee_wallet_amount; // 3.00% fee wallet
-        distribution_state.monthly_expense_fees = expense_wallet_amount; // 0.33% expense wallet
-        distribution_state.monthly_marketing_fees = marketing_wallet_amount; // 0.03% marketing wallet
-        distribution_state.monthly_burn_fees = burn_wallet_amount;
-        distribution_state.distribution_round += 1;
-        distribution_state.last_distribution_time = clock.unix_timestamp; // Update timestamp for next check
-        
-        msg!("Monthly RR3 rewards recorded: {} RR3 tokens total allocated", 
-            total_rr3_for_rewards as f64 / 100_000_000.0
-        );
-        msg!("Fee wallet: {} RR3, Expense: {} RR3, Marketing: {} RR3, Burn: {} RR3", 
-            fee_wallet_amount as f64 / 100_000_000.0,
-            expense_wallet_amount as f64 / 100_000_000.0,
-            marketing_wallet_amount as f64 / 100_000_000.0,
-            burn_wallet_amount as f64 / 100_000_000.0
-        );
-        msg!("Distribution round: {}", distribution_state.distribution_round);
-        
+        distribution_state.pending_admin = new_admin;
+
+        emit!(AdminProposed {
+            pool: ctx.accounts.pool.key(),
+            admin: distribution_state.admin,
+            pending_admin: new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Admin transfer proposed to {}", new_admin);
         Ok(())
     }
 
-    // Admin function: Update total staked amount for current distribution (supports weighted staking)
-    // Note: For weighted rewards, this should be the sum of (amount * lock_multiplier / 100) for all stakes
-    pub fn update_total_staked(
-        ctx: Context<UpdateTotalStaked>,
-        total_weighted_staked: u64,
+    // Pending admin accepts the admin role
+    pub fn accept_admin(
+        ctx: Context<AcceptAdmin>,
     ) -> Result<()> {
         let distribution_state = &mut ctx.accounts.distribution_state;
-        
-        distribution_state.total_rr3_staked = total_weighted_staked;
-        
-        msg!("Updated total weighted RR3 staked: {} tokens", total_weighted_staked as f64 / 100_000_000.0);
-        msg!("Note: This should be the sum of weighted stakes (amount * multiplier) for accurate distribution");
+        let previous_admin = distribution_state.admin;
+
+        distribution_state.admin = ctx.accounts.pending_admin.key();
+        distribution_state.pending_admin = Pubkey::default();
+
+        emit!(AdminTransferred {
+            pool: ctx.accounts.pool.key(),
+            previous_admin,
+            new_admin: distribution_state.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Admin transferred from {} to {}", previous_admin, distribution_state.admin);
         Ok(())
     }
 
-    // Calculate and assign monthly RR3 rewards to individual staker (weighted by lock duration)
-    pub fn assign_monthly_rewards(
-        ctx: Context<AssignMonthlyRewards>,
-        stake_index: u32,
+    // Admin function: Delegate the distributor, treasurer and pauser roles
+    pub fn set_roles(
+        ctx: Context<SetRoles>,
+        distributor: Pubkey,
+        treasurer: Pubkey,
+        pauser: Pubkey,
     ) -> Result<()> {
-        let distribution_state = &ctx.accounts.distribution_state;
-        let stake_record = &mut ctx.accounts.stake_record;
-        let clock = Clock::get()?;
-        
+        let distribution_state = &mut ctx.accounts.distribution_state;
+
+        distribution_state.distributor = distributor;
+        distribution_state.treasurer = treasurer;
+        distribution_state.pauser = pauser;
+
+        emit!(RolesUpdated {
+            pool: ctx.accounts.pool.key(),
+            distributor,
+            treasurer,
+            pauser,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Roles set - Distributor: {}, Treasurer: {}, Pauser: {}", distributor, treasurer, pauser);
+        Ok(())
+    }
+
+    // Pauser function: Set the pause flags (PAUSE_STAKING | PAUSE_CLAIMING | PAUSE_UNSTAKING | PAUSE_DISTRIBUTION)
+    pub fn set_paused(
+        ctx: Context<SetPaused>,
+        paused: u8,
+    ) -> Result<()> {
+        let distribution_state = &mut ctx.accounts.distribution_state;
+
+        require!(paused & !PAUSE_ALL == 0, StakeError::InvalidPauseFlags);
+        distribution_state.paused = paused;
+
+        emit!(PauseFlagsUpdated {
+            pool: ctx.accounts.pool.key(),
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Pause flags set to {:#06b}", paused);
+        Ok(())
+    }
+
+    // Admin function: Add a new lock tier
+    pub fn add_lock_tier(
+        ctx: Context<UpdateStakingConfig>,
+        lock_duration: i64,
+        monthly_weight_bps: u64,
+        completion_multiplier_bps: u64,
+    ) -> Result<()> {
+        let staking_config = &mut ctx.accounts.staking_config;
+
+        validate_lock_tier(lock_duration, monthly_weight_bps, completion_multiplier_bps)?;
         require!(
-            distribution_state.monthly_rr3_for_rewards > 0,
-            StakeError::NoMonthlyCollection
+            staking_config.find_tier(lock_duration).is_none(),
+            StakeError::LockTierAlreadyExists
         );
         require!(
-            distribution_state.total_rr3_staked > 0,
-            StakeError::NoStakersForDistribution
+            staking_config.tiers.len() < MAX_LOCK_TIERS,
+            StakeError::TooManyLockTiers
         );
-        
-        // Prevent double-rewarding: ensure stake hasn't already received rewards for this round
-        require!(
-            stake_record.last_distribution_round < distribution_state.distribution_round,
-            StakeError::AlreadyReceivedRewardsThisRound
+
+        staking_config.tiers.push(LockTier {
+            lock_duration,
+            monthly_weight_bps,
+            completion_multiplier_bps,
+            enabled: true,
+        });
+
+        emit!(LockTierChanged {
+            pool: ctx.accounts.pool.key(),
+            lock_duration,
+            monthly_weight_bps,
+            completion_multiplier_bps,
+            enabled: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Lock tier added: {} seconds, monthly weight: {} bps, completion multiplier: {} bps",
+            lock_duration, monthly_weight_bps, completion_multiplier_bps
         );
+        Ok(())
+    }
 
-        // Calculate lock duration multiplier (basis points for precision)
-        // Base: 100 = 1.0x, higher values = minimal bonus rewards (conservative approach)
-        let lock_multiplier = match stake_record.lock_duration {
-            LOCK_3_MONTHS => 100,  // 1.0000x - base multiplier
-            LOCK_6_MONTHS => 101,  // 1.0100x - 1% bonus
-            LOCK_1_YEAR => 102,    // 1.0200x - 2% bonus
-            LOCK_2_YEARS => 103,   // 1.0300x - 3% bonus
-            LOCK_3_YEARS => 103,   // 1.0333x - 3.33% bonus (rounded to 103)
-            _ => {
-                // For development/testing periods (< 3 months), use proportional multiplier
-                let months = stake_record.lock_duration / (30 * 24 * 60 * 60);
-                if months == 0 {
-                    50  // Less than 1 month: 0.5x
-                } else {
-                    (100 + (months as u64 * 8)).min(100) // Proportional up to 3 months
-                }
-            }
-        };
+    // Admin function: Retire a lock tier (no new stakes; existing stakes keep their terms)
+    pub fn retire_lock_tier(
+        ctx: Context<UpdateStakingConfig>,
+        lock_duration: i64,
+    ) -> Result<()> {
+        let staking_config = &mut ctx.accounts.staking_config;
+
+        let tier = staking_config.tiers
+            .iter_mut()
+            .find(|tier| tier.lock_duration == lock_duration)
+            .ok_or(StakeError::LockTierNotFound)?;
+        tier.enabled = false;
+
+        emit!(LockTierChanged {
+            pool: ctx.accounts.pool.key(),
+            lock_duration,
+            monthly_weight_bps: tier.monthly_weight_bps,
+            completion_multiplier_bps: tier.completion_multiplier_bps,
+            enabled: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Lock tier retired: {} seconds", lock_duration);
+        Ok(())
+    }
 
-        msg!("Lock duration: {} seconds, Multiplier: {}x", 
-            stake_record.lock_duration, 
-            lock_multiplier as f64 / 100.0
+    // Admin function: Edit a lock tier (applies to new stakes only; existing stakes keep their terms)
+    pub fn update_lock_tier(
+        ctx: Context<UpdateStakingConfig>,
+        lock_duration: i64,
+        monthly_weight_bps: u64,
+        completion_multiplier_bps: u64,
+        enabled: bool,
+    ) -> Result<()> {
+        let staking_config = &mut ctx.accounts.staking_config;
+
+        validate_lock_tier(lock_duration, monthly_weight_bps, completion_multiplier_bps)?;
+        let tier = staking_config.tiers
+            .iter_mut()
+            .find(|tier| tier.lock_duration == lock_duration)
+            .ok_or(StakeError::LockTierNotFound)?;
+        tier.monthly_weight_bps = monthly_weight_bps;
+        tier.completion_multiplier_bps = completion_multiplier_bps;
+        tier.enabled = enabled;
+
+        emit!(LockTierChanged {
+            pool: ctx.accounts.pool.key(),
+            lock_duration,
+            monthly_weight_bps,
+            completion_multiplier_bps,
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Lock tier updated: {} seconds, monthly weight: {} bps, completion multiplier: {} bps, enabled: {}",
+            lock_duration, monthly_weight_bps, completion_multiplier_bps, enabled
         );
+        Ok(())
+    }
 
-        // Calculate weighted stake amount
-        let weighted_amount = (stake_record.amount as u128)
-            .checked_mul(lock_multiplier as u128).unwrap()
-            .checked_div(100).unwrap();
+    // Admin function: Set the emergency buyback discount to principal
+    pub fn set_buyback_discount(
+        ctx: Context<UpdateStakingConfig>,
+        buyback_discount_bps: u64,
+    ) -> Result<()> {
+        let staking_config = &mut ctx.accounts.staking_config;
 
-        // Calculate weighted total staked (NOTE: This needs to be updated in update_total_staked to include weights)
-        // For now, we use the existing total and apply the same logic
-        let weighted_total = distribution_state.total_rr3_staked as u128;
+        require!(buyback_discount_bps <= BPS_DENOMINATOR, StakeError::InvalidBuybackDiscount);
+        staking_config.buyback_discount_bps = buyback_discount_bps;
 
-        // Calculate proportional reward: (weighted_stake / weighted_total) * monthly_rr3_rewards
-        let user_share = weighted_amount
-            .checked_mul(distribution_state.monthly_rr3_for_rewards as u128).unwrap()
-            .checked_div(weighted_total).unwrap();
+        emit!(BuybackDiscountUpdated {
+            pool: ctx.accounts.pool.key(),
+            buyback_discount_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-        let reward_amount = user_share as u64;
+        msg!("Buyback discount set to {} bps", buyback_discount_bps);
+        Ok(())
+    }
 
-        // Add to pending rewards
-        stake_record.pending_rr3_rewards += reward_amount;
-        stake_record.last_distribution_round = distribution_state.distribution_round;
-        
-        msg!("Assigned {} RR3 rewards to staker {} (stake: {}, weighted: {}, multiplier: {}x)",
-            reward_amount as f64 / 100_000_000.0,
-            stake_record.user,
-            stake_record.amount as f64 / 100_000_000.0,
-            weighted_amount as f64 / 100_000_000.0,
-            lock_multiplier as f64 / 100.0
+    // Admin function: Set the minimum time between distribution rounds (at least MIN_ROUND_INTERVAL)
+    pub fn set_round_interval(
+        ctx: Context<UpdateStakingConfig>,
+        round_interval: i64,
+    ) -> Result<()> {
+        let staking_config = &mut ctx.accounts.staking_config;
+
+        require!(round_interval >= MIN_ROUND_INTERVAL, StakeError::InvalidRoundInterval);
+        staking_config.round_interval = round_interval;
+
+        emit!(RoundIntervalUpdated {
+            pool: ctx.accounts.pool.key(),
+            round_interval,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Round interval set to {} seconds", round_interval);
+        Ok(())
+    }
+
+    // Admin function: Set the tip paid from the keeper incentive pool per stake credited by crank_rewards
+    pub fn set_keeper_tip(
+        ctx: Context<UpdateStakingConfig>,
+        keeper_tip_per_stake: u64,
+    ) -> Result<()> {
+        let staking_config = &mut ctx.accounts.staking_config;
+
+        staking_config.keeper_tip_per_stake = keeper_tip_per_stake;
+
+        emit!(KeeperTipUpdated {
+            pool: ctx.accounts.pool.key(),
+            keeper_tip_per_stake,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Keeper tip set to {} per stake", format_ui_amount(keeper_tip_per_stake, ctx.accounts.pool.decimals));
+        Ok(())
+    }
+
+    // Treasurer function: Configure the RR3 token accounts receiving the staking commission
+    pub fn set_commission_wallets(
+        ctx: Context<SetCommissionWallets>,
+    ) -> Result<()> {
+        let distribution_state = &mut ctx.accounts.distribution_state;
+
+        distribution_state.fee_wallet = ctx.accounts.fee_wallet_token_bag.key();
+        distribution_state.expense_wallet = ctx.accounts.expense_wallet_token_bag.key();
+        distribution_state.marketing_wallet = ctx.accounts.marketing_wallet_token_bag.key();
+
+        emit!(CommissionWalletsUpdated {
+            pool: ctx.accounts.pool.key(),
+            fee_wallet: distribution_state.fee_wallet,
+            expense_wallet: distribution_state.expense_wallet,
+            marketing_wallet: distribution_state.marketing_wallet,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Commission wallets set - Fee: {}, Expense: {}, Marketing: {}",
+            distribution_state.fee_wallet,
+            distribution_state.expense_wallet,
+            distribution_state.marketing_wallet
         );
-        
         Ok(())
     }
 
+    // Admin function: Send round leftovers to a token account instead of carrying them into the next round
+    // Omit the sink account to carry leftovers over again
+    pub fn set_carryover_sink(
+        ctx: Context<SetCarryoverSink>,
+    ) -> Result<()> {
+        let distribution_state = &mut ctx.accounts.distribution_state;
 
-    // Admin function: Update total staked amount for current distribution (supports weighted staking)
+        distribution_state.carryover_sink = ctx.accounts.carryover_sink_token_bag
+            .as_ref()
+            .map_or(Pubkey::default(), |sink| sink.key());
+
+        emit!(CarryoverSinkUpdated {
+            pool: ctx.accounts.pool.key(),
+            carryover_sink: distribution_state.carryover_sink,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Carryover sink set to {}", distribution_state.carryover_sink);
+        Ok(())
+    }
+
+    // Distributor function: Record monthly RR3 allocation for reward distribution
+    // The allocation must already sit in the rewards vault (see fund_and_record_round)
+    pub fn record_monthly_collection(
+        ctx: Context<RecordMonthlyCollection>,
+        total_rr3_for_rewards: u64, // Total RR3 tokens allocated for rewards (in RR3 token units)
+    ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_DISTRIBUTION)?;
+
+        record_round(
+            &ctx.accounts.pool,
+            &mut ctx.accounts.distribution_state,
+            &mut ctx.accounts.reward_round,
+            ctx.accounts.previous_reward_round.as_deref().map(|round| &**round),
+            ctx.accounts.staking_config.round_interval,
+            ctx.accounts.rewards_vault.amount,
+            total_rr3_for_rewards,
+        )
+    }
+
+    // Distributor function: Deposit RR3 into the rewards vault and record it as the next round in one instruction
+    // The round's allocation is exactly what arrived in the vault, so it is always backed by real tokens
+    pub fn fund_and_record_round(
+        ctx: Context<FundAndRecordRound>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_DISTRIBUTION)?;
+        require!(amount > 0, StakeError::InvalidFundingAmount);
+
+        let received_amount = transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.distributor_rr3_token_bag,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.distributor,
+            &ctx.accounts.rewards_vault,
+            amount,
+        )?;
+        ctx.accounts.rewards_vault.reload()?;
+
+        emit!(RewardsFunded {
+            pool: ctx.accounts.pool.key(),
+            round: ctx.accounts.distribution_state.distribution_round + 1,
+            funder: ctx.accounts.distributor.key(),
+            amount: received_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_round(
+            &ctx.accounts.pool,
+            &mut ctx.accounts.distribution_state,
+            &mut ctx.accounts.reward_round,
+            ctx.accounts.previous_reward_round.as_deref().map(|round| &**round),
+            ctx.accounts.staking_config.round_interval,
+            ctx.accounts.rewards_vault.amount,
+            received_amount,
+        )
+    }
 
-    // Admin function: Transfer marketing fees to marketing wallet
-    pub fn transfer_marketing_fees(
-        ctx: Context<TransferMarketingFees>,
+    // Distributor function: Publish the next round as a Merkle root of off-chain computed rewards
+    // Alternative to record_monthly_collection for very large staker sets; stakers claim with claim_with_proof
+    pub fn publish_reward_root(
+        ctx: Context<PublishRewardRoot>,
+        round: u64,
+        root: [u8; 32],
+        total: u64,
+        leaf_count: u32,
     ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_DISTRIBUTION)?;
+
         let distribution_state = &mut ctx.accounts.distribution_state;
-        
+        let reward_root = &mut ctx.accounts.reward_root;
+        let clock = Clock::get()?;
+
+        // Merkle rounds share the round sequence and cadence of record_monthly_collection
+        require!(round == distribution_state.distribution_round + 1, StakeError::InvalidRound);
+        distribution_state.require_round_interval_elapsed(ctx.accounts.staking_config.round_interval, clock.unix_timestamp)?;
+        distribution_state.require_previous_round_closed(ctx.accounts.previous_reward_round.as_deref().map(|round| &**round))?;
         require!(
-            distribution_state.monthly_marketing_fees > 0,
-            StakeError::NoMarketingFeesToTransfer
+            leaf_count > 0 && leaf_count <= MAX_MERKLE_LEAVES,
+            StakeError::InvalidLeafCount
+        );
+        // Tokens owed to earlier rounds don't back this one
+        distribution_state.commit_rewards(total, ctx.accounts.rewards_vault.amount)?;
+
+        reward_root.round = round;
+        reward_root.root = root;
+        reward_root.total_amount = total;
+        reward_root.claimed_amount = 0;
+        reward_root.leaf_count = leaf_count;
+        reward_root.published_at = clock.unix_timestamp;
+        reward_root.claimed_bitmap = vec![0; (leaf_count as usize).div_ceil(8)];
+
+        distribution_state.distribution_round = round;
+        distribution_state.last_distribution_time = clock.unix_timestamp;
+
+        ctx.accounts.reward_round.open(
+            round,
+            total,
+            distribution_state.total_rr3_staked,
+            0,
+            true,
+            clock.unix_timestamp,
         );
 
-        let marketing_amount = distribution_state.monthly_marketing_fees;
+        emit!(RewardRootPublished {
+            pool: ctx.accounts.pool.key(),
+            round,
+            root,
+            total_amount: total,
+            leaf_count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Published Merkle reward root for round {}: {} RR3 across {} leaves",
+            round,
+            format_ui_amount(total, ctx.accounts.pool.decimals),
+            leaf_count
+        );
+        Ok(())
+    }
+
+    // Claim a Merkle round reward for one stake
+    // Leaf = keccak(0x00 || leaf_index (le) || user || stake_index (le) || amount (le)), node = keccak(0x01 || left || right);
+    // leaf_index is the leaf's position in the tree and selects its claimed bit
+    pub fn claim_with_proof(
+        ctx: Context<ClaimWithProof>,
+        round: u64,
+        stake_index: u32,
+        leaf_index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_CLAIMING)?;
 
-        // Transfer SOL from treasury to marketing wallet
-        **ctx.accounts.treasury_sol_account.lamports.borrow_mut() -= marketing_amount;
-        **ctx.accounts.marketing_wallet_sol_account.lamports.borrow_mut() += marketing_amount;
+        let reward_root = &mut ctx.accounts.reward_root;
+        let user = ctx.accounts.user_authority.key();
 
-        // Reset marketing fees to 0
-        distribution_state.monthly_marketing_fees = 0;
-        
-        msg!("Transferred {} SOL to marketing wallet", marketing_amount as f64 / 1_000_000_000.0);
+        require!(ctx.accounts.reward_round.closed_at == 0, StakeError::RoundAlreadyClosed);
+        // Bounds-check the index before it is used to read the claimed bitmap
+        require!(leaf_index < reward_root.leaf_count, StakeError::InvalidMerkleProof);
+        require!(!reward_root.is_claimed(leaf_index), StakeError::RewardAlreadyClaimed);
+
+        let leaf = merkle_leaf(leaf_index, &user, stake_index, amount);
+        require!(
+            verify_merkle_proof(&proof, reward_root.root, leaf, leaf_index, reward_root.leaf_count),
+            StakeError::InvalidMerkleProof
+        );
+
+        reward_root.set_claimed(leaf_index);
+        reward_root.claimed_amount = reward_root.claimed_amount.checked_add(amount).unwrap();
+        require!(
+            reward_root.claimed_amount <= reward_root.total_amount,
+            StakeError::InsufficientTreasuryFunds
+        );
+        ctx.accounts.reward_round.record_assignment(amount);
+        ctx.accounts.distribution_state.release_rewards(amount);
+
+        let received_amount = transfer_from_pool_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            PoolVault::Rewards,
+            &ctx.accounts.rewards_vault,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.user_rr3_token_bag,
+            amount,
+        )?;
+
+        emit!(MerkleRewardClaimed {
+            pool: ctx.accounts.pool.key(),
+            user,
+            stake_index,
+            round,
+            leaf_index,
+            amount: received_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Claimed {} RR3 for stake #{} from Merkle round {}",
+            format_ui_amount(received_amount, ctx.accounts.pool.decimals),
+            stake_index,
+            round
+        );
         Ok(())
     }
 
-    // Admin function: Transfer fee wallet funds (3.00%)
-    pub fn transfer_fee_wallet(
-        ctx: Context<TransferFeeWallet>,
+    // Admin function: Reconcile an off-chain computed weighted total against the on-chain value
+    // The weighted total is maintained by stake, add_stake and unstake; this only verifies it
+    pub fn reconcile_total_staked(
+        ctx: Context<ReconcileTotalStaked>,
+        expected_total_weighted_staked: u64,
     ) -> Result<()> {
-        let distribution_state = &mut ctx.accounts.distribution_state;
-        
+        let distribution_state = &ctx.accounts.distribution_state;
+
+        msg!("On-chain total weighted RR3 staked: {}, expected: {}",
+            distribution_state.total_rr3_staked,
+            expected_total_weighted_staked
+        );
         require!(
-            distribution_state.monthly_rr3_for_rewards > 0,
-            StakeError::NoFeeWalletFeesToTransfer
+            distribution_state.total_rr3_staked == expected_total_weighted_staked,
+            StakeError::TotalStakedMismatch
         );
+        Ok(())
+    }
+
+    // Settle accumulated RR3 rewards into an individual stake's pending rewards (weighted by lock duration)
+    // Optional: claim_rr3_rewards, add_stake and unstake settle lazily on their own
+    pub fn assign_monthly_rewards(
+        ctx: Context<AssignMonthlyRewards>,
+        _stake_index: u32,
+    ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_DISTRIBUTION)?;
+
+        let decimals = ctx.accounts.pool.decimals;
+        let distribution_state = &ctx.accounts.distribution_state;
+        let stake_record = &mut ctx.accounts.stake_record;
 
-        let fee_wallet_amount = distribution_state.monthly_rr3_for_rewards;
+        // Only open accumulator rounds are settled here; Merkle rounds pay through claim_with_proof
+        require!(!ctx.accounts.reward_round.merkle, StakeError::MerkleRoundNotAssignable);
+        require!(ctx.accounts.reward_round.closed_at == 0, StakeError::RoundAlreadyClosed);
+        
+        // Prevent double-rewarding: ensure stake hasn't already been settled for this round
+        require!(
+            stake_record.last_distribution_round < distribution_state.distribution_round,
+            StakeError::AlreadyReceivedRewardsThisRound
+        );
 
-        // Transfer SOL from treasury to fee wallet
-        **ctx.accounts.treasury_sol_account.lamports.borrow_mut() -= fee_wallet_amount;
-        **ctx.accounts.fee_wallet_sol_account.lamports.borrow_mut() += fee_wallet_amount;
+        // Weight fixed from the lock tier when the stake was created
+        let lock_multiplier = stake_record.monthly_weight_bps;
+        let weighted_amount = weighted_stake_amount(stake_record.amount, stake_record.monthly_weight_bps);
 
-        // Reset fee wallet amount to 0
-        distribution_state.monthly_rr3_for_rewards = 0;
+        let reward_amount = settle_stake_rewards(stake_record, distribution_state.acc_reward_per_weighted_share);
+        stake_record.last_distribution_round = distribution_state.distribution_round;
+        // The settled amount can include earlier rounds the stake never settled - the round only books its own share
+        let round_share = ctx.accounts.reward_round.round_share(weighted_amount);
+        ctx.accounts.reward_round.record_assignment(round_share);
+
+        emit!(RewardsAssigned {
+            pool: ctx.accounts.pool.key(),
+            user: stake_record.user,
+            stake_index: stake_record.stake_index,
+            amount: reward_amount,
+            round: distribution_state.distribution_round,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Assigned {} RR3 rewards to staker {} (stake: {}, weighted: {}, multiplier: {}x)",
+            format_ui_amount(reward_amount, decimals),
+            stake_record.user,
+            format_ui_amount(stake_record.amount, decimals),
+            format_ui_amount(weighted_amount, decimals),
+            format_bps_multiplier(lock_multiplier)
+        );
         
-        msg!("Transferred {} SOL to fee wallet", fee_wallet_amount as f64 / 1_000_000_000.0);
         Ok(())
     }
 
-    // Admin function: Burn fees (transfer SOL to burn wallet)
-    pub fn burn_fees(
-        ctx: Context<BurnFees>,
+    // Permissionless crank: settle the current round's rewards into up to MAX_CRANK_STAKES stake records
+    // passed as writable remaining_accounts; the keeper is tipped from the keeper incentive pool
+    pub fn crank_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrankRewards<'info>>,
     ) -> Result<()> {
-        let distribution_state = &mut ctx.accounts.distribution_state;
-        
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_DISTRIBUTION)?;
+
         require!(
-            distribution_state.monthly_burn_fees > 0,
-            StakeError::NoBurnFeesToTransfer
+            ctx.remaining_accounts.len() <= MAX_CRANK_STAKES,
+            StakeError::TooManyCrankStakes
         );
+        require!(!ctx.accounts.reward_round.merkle, StakeError::MerkleRoundNotAssignable);
+        require!(ctx.accounts.reward_round.closed_at == 0, StakeError::RoundAlreadyClosed);
+
+        let pool_key = ctx.accounts.pool.key();
+        let distribution_state = &ctx.accounts.distribution_state;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut stakes_credited: u64 = 0;
+        let mut total_rewards: u64 = 0;
+
+        for stake_info in ctx.remaining_accounts.iter() {
+            // Must be a writable stake record owned by this program
+            require!(
+                stake_info.owner == ctx.program_id && stake_info.is_writable,
+                StakeError::InvalidStakeAccount
+            );
+            let mut stake_record = StakeRecord::try_deserialize(&mut &stake_info.try_borrow_data()?[..])?;
+
+            // ...and the stake PDA of this pool for its recorded user and index
+            let (expected_stake_key, _) = Pubkey::find_program_address(
+                &[
+                    b"stake",
+                    pool_key.as_ref(),
+                    stake_record.user.as_ref(),
+                    stake_record.stake_index.to_le_bytes().as_ref(),
+                ],
+                ctx.program_id,
+            );
+            require_keys_eq!(stake_info.key(), expected_stake_key, StakeError::InvalidStakeAccount);
 
-        let burn_amount = distribution_state.monthly_burn_fees;
+            // Already settled for this round (e.g. by another keeper) - skip instead of failing the batch
+            if stake_record.last_distribution_round >= distribution_state.distribution_round {
+                continue;
+            }
 
-        // Transfer SOL to burn wallet (or implement actual burn if preferred)
-        **ctx.accounts.treasury_sol_account.lamports.borrow_mut() -= burn_amount;
-        **ctx.accounts.burn_wallet_sol_account.lamports.borrow_mut() += burn_amount;
+            let reward_amount = settle_stake_rewards(&mut stake_record, distribution_state.acc_reward_per_weighted_share);
+            stake_record.last_distribution_round = distribution_state.distribution_round;
+            stake_record.try_serialize(&mut &mut stake_info.try_borrow_mut_data()?[..])?;
 
-        // Reset burn fees to 0
-        distribution_state.monthly_burn_fees = 0;
-        
-        msg!("Transferred {} SOL to burn wallet", burn_amount as f64 / 1_000_000_000.0);
+            // Only stakes that actually received rewards earn the keeper a tip
+            if reward_amount > 0 {
+                stakes_credited += 1;
+            }
+            total_rewards = total_rewards.checked_add(reward_amount).unwrap();
+            // The round only books its own share, not earlier rounds the stake never settled
+            let weighted_amount = weighted_stake_amount(stake_record.amount, stake_record.monthly_weight_bps);
+            let round_share = ctx.accounts.reward_round.round_share(weighted_amount);
+            ctx.accounts.reward_round.record_assignment(round_share);
+
+            emit!(RewardsAssigned {
+                pool: pool_key,
+                user: stake_record.user,
+                stake_index: stake_record.stake_index,
+                amount: reward_amount,
+                round: distribution_state.distribution_round,
+                timestamp,
+            });
+        }
+
+        // Tip the keeper for each stake credited, capped by what the incentive pool holds
+        let keeper_tip = ctx.accounts.staking_config.keeper_tip_per_stake
+            .checked_mul(stakes_credited).unwrap()
+            .min(ctx.accounts.keeper_incentive_bag.amount);
+        if keeper_tip > 0 {
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.keeper_incentive_bag,
+                &ctx.accounts.rr3_mint,
+                &[b"keeper_incentive".as_ref(), pool_key.as_ref(), &[ctx.bumps.keeper_incentive_bag]],
+                &ctx.accounts.keeper_rr3_token_bag,
+                keeper_tip,
+            )?;
+        }
+
+        emit!(RewardsCranked {
+            pool: pool_key,
+            keeper: ctx.accounts.keeper.key(),
+            round: distribution_state.distribution_round,
+            stakes_credited,
+            total_rewards,
+            keeper_tip,
+            timestamp,
+        });
+
+        msg!("Crank credited {} stakes with {} RR3 rewards, keeper tip: {} RR3",
+            stakes_credited,
+            format_ui_amount(total_rewards, ctx.accounts.pool.decimals),
+            format_ui_amount(keeper_tip, ctx.accounts.pool.decimals)
+        );
         Ok(())
     }
 
-    // Helper function to get lock period description
+    // Admin function: Update total staked amount for current distribution (supports weighted staking)
+
+    // View: Lock tier for a lock duration (None if there is no such tier)
     pub fn get_lock_period_info(
         ctx: Context<GetLockPeriodInfo>,
         lock_duration_seconds: i64,
-    ) -> Result<()> {
-        let (period_name, multiplier) = match lock_duration_seconds {
-            LOCK_3_MONTHS => ("3 months", 100),
-            LOCK_6_MONTHS => ("6 months", 125), 
-            LOCK_1_YEAR => ("1 year", 150),
-            LOCK_2_YEARS => ("2 years", 200),
-            LOCK_3_YEARS => ("3 years", 300),
-            _ => ("Invalid lock period", 0),
-        };
-        
-        msg!("Lock period: {} ({} seconds)", period_name, lock_duration_seconds);
-        msg!("Reward multiplier: {}x", multiplier as f64 / 100.0);
+    ) -> Result<Option<LockTier>> {
+        let staking_config = &ctx.accounts.staking_config;
+
+        match staking_config.find_tier(lock_duration_seconds) {
+            Some(tier) => {
+                msg!("Lock period: {} seconds ({})", tier.lock_duration, if tier.enabled { "available" } else { "retired" });
+                msg!("Monthly reward multiplier: {}x", format_bps_multiplier(tier.monthly_weight_bps));
+                msg!("Completion multiplier: {}x", format_bps_multiplier(tier.completion_multiplier_bps));
+            }
+            None => msg!("Invalid lock period: {} seconds", lock_duration_seconds),
+        }
+
         msg!("Available periods:");
-        msg!("  - 3 months: 1.0x multiplier");
-        msg!("  - 6 months: 1.25x multiplier (+25% rewards)");
-        msg!("  - 1 year: 1.5x multiplier (+50% rewards)");
-        msg!("  - 2 years: 2.0x multiplier (+100% rewards)");
-        msg!("  - 3 years: 3.0x multiplier (+200% rewards)");
-        Ok(())
+        for tier in staking_config.tiers.iter().filter(|tier| tier.enabled) {
+            msg!("  - {} seconds: {}x monthly multiplier, {}x completion multiplier",
+                tier.lock_duration,
+                format_bps_multiplier(tier.monthly_weight_bps),
+                format_bps_multiplier(tier.completion_multiplier_bps)
+            );
+        }
+        Ok(staking_config.find_tier(lock_duration_seconds).copied())
     }
 
-    // Admin function: Complete monthly distribution (reset for next month)
+    // Distributor function: Complete monthly distribution (reset for next month)
     pub fn complete_monthly_distribution(
         ctx: Context<CompleteMonthlyDistribution>,
     ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_DISTRIBUTION)?;
+
         let distribution_state = &mut ctx.accounts.distribution_state;
+        let reward_round = &mut ctx.accounts.reward_round;
         let clock = Clock::get()?;
+
+        require!(reward_round.closed_at == 0, StakeError::RoundAlreadyClosed);
+
+        // Leftover of the round: accumulator rounds leave the allocation the accumulator can never pay out
+        // because of integer rounding (unsettled stakes keep their share); Merkle rounds leave what was
+        // not claimed, and closing the round ends its claims
+        let leftover = if reward_round.merkle {
+            let reward_root = ctx.accounts.reward_root
+                .as_ref()
+                .ok_or(StakeError::RewardRootRequired)?;
+            reward_root.total_amount.saturating_sub(reward_root.claimed_amount)
+        } else {
+            let distributable = reward_round.acc_reward_increment
+                .checked_mul(reward_round.total_weighted_staked as u128).unwrap()
+                .checked_div(ACC_REWARD_PRECISION).unwrap() as u64;
+            reward_round.allocated_amount.saturating_sub(distributable)
+        };
+        reward_round.undistributed_dust = leftover;
+        distribution_state.release_rewards(leftover);
+        reward_round.closed_at = clock.unix_timestamp;
+
+        // Roll the leftover into the next round, or send it to the configured sink
+        roll_over_rewards(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            &ctx.accounts.rewards_vault,
+            &ctx.accounts.rr3_mint,
+            ctx.accounts.carryover_sink_token_bag.as_deref(),
+            distribution_state,
+            leftover,
+        )?;
         
         distribution_state.last_distribution_time = clock.unix_timestamp;
         distribution_state.monthly_rr3_for_rewards = 0;
+
+        emit!(RoundCompleted {
+            pool: ctx.accounts.pool.key(),
+            round: distribution_state.distribution_round,
+            allocated_amount: reward_round.allocated_amount,
+            amount_assigned: reward_round.amount_assigned,
+            stakes_assigned: reward_round.stakes_assigned,
+            undistributed_dust: reward_round.undistributed_dust,
+            timestamp: clock.unix_timestamp,
+        });
         
-        msg!("Monthly distribution completed for round {}", distribution_state.distribution_round);
+        msg!("Monthly distribution completed for round {}, leftover: {} RR3",
+            distribution_state.distribution_round,
+            format_ui_amount(leftover, ctx.accounts.pool.decimals)
+        );
         Ok(())
     }
  
 
     pub fn create_rr3_token_bag(
-        _ctx: Context<CreateRR3TokenBag>
+        ctx: Context<CreateRR3TokenBag>
     ) -> Result<()> {
+        ctx.accounts.pool.principal_vault_bump = ctx.bumps.principal_vault;
+        emit!(VaultCreated {
+            pool: ctx.accounts.pool.key(),
+            vault: ctx.accounts.principal_vault.key(),
+            kind: VaultKind::Principal,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         msg!("RR3 Staking Bag created");
         Ok(())
     }
 
     // Create treasury bag for reward distribution
     pub fn create_treasury_bag(
-        _ctx: Context<CreateTreasuryBag>
+        ctx: Context<CreateTreasuryBag>
     ) -> Result<()> {
+        ctx.accounts.pool.rewards_vault_bump = ctx.bumps.rewards_vault;
+        emit!(VaultCreated {
+            pool: ctx.accounts.pool.key(),
+            vault: ctx.accounts.rewards_vault.key(),
+            kind: VaultKind::Rewards,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         msg!("RR3 Treasury Bag created for reward distribution");
         Ok(())
     }
 
+    // Create bonus reserve vault backing completion bonuses
+    pub fn create_bonus_reserve(
+        ctx: Context<CreateBonusReserve>
+    ) -> Result<()> {
+        ctx.accounts.pool.bonus_reserve_bump = ctx.bumps.bonus_reserve_vault;
+        emit!(VaultCreated {
+            pool: ctx.accounts.pool.key(),
+            vault: ctx.accounts.bonus_reserve_vault.key(),
+            kind: VaultKind::BonusReserve,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("RR3 Bonus Reserve created for completion bonuses");
+        Ok(())
+    }
+
+    // Create keeper incentive pool funding crank_rewards tips
+    pub fn create_keeper_incentive_pool(
+        ctx: Context<CreateKeeperIncentivePool>
+    ) -> Result<()> {
+        emit!(VaultCreated {
+            pool: ctx.accounts.pool.key(),
+            vault: ctx.accounts.keeper_incentive_bag.key(),
+            kind: VaultKind::KeeperIncentive,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("RR3 Keeper Incentive Pool created for crank tips");
+        Ok(())
+    }
+
     // Create buyback treasury for emergency buyback guarantee
     pub fn create_buyback_treasury(
-        _ctx: Context<CreateBuybackTreasury>
+        ctx: Context<CreateBuybackTreasury>
     ) -> Result<()> {
+        emit!(VaultCreated {
+            pool: ctx.accounts.pool.key(),
+            vault: ctx.accounts.buyback_treasury_bag.key(),
+            kind: VaultKind::BuybackTreasury,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         msg!("Buyback treasury created for emergency buyback guarantee");
         Ok(())
     }
@@ -337,30 +1003,22 @@ pub mod staker {
     // Stake tokens (supports multiple stakes per user)
     pub fn stake(
         ctx: Context<Stake>,
-        _program_rr3_bag_bump: u8,
         stake_index: u32,
         rr3_amount: u64,
         lock_duration_seconds: i64,
     ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_STAKING)?;
+
         let stake_record = &mut ctx.accounts.stake_record;
         let user_stake_counter = &mut ctx.accounts.user_stake_counter;
+        let distribution_state = &mut ctx.accounts.distribution_state;
         let clock = Clock::get()?;
 
-        msg!("Creating stake #{} with {} RR3 tokens and {} seconds lock", stake_index, rr3_amount, lock_duration_seconds);
+        msg!("Creating stake #{} with {} RR3 tokens and {} seconds lock", stake_index, format_ui_amount(rr3_amount, ctx.accounts.pool.decimals), lock_duration_seconds);
 
-        // Validate lock duration - allow development testing periods
-        let is_professional_period = lock_duration_seconds == LOCK_3_MONTHS ||
-            lock_duration_seconds == LOCK_6_MONTHS ||
-            lock_duration_seconds == LOCK_1_YEAR ||
-            lock_duration_seconds == LOCK_2_YEARS ||
-            lock_duration_seconds == LOCK_3_YEARS;
-        
-        let is_development_period = lock_duration_seconds >= 300; // Allow 5+ minutes for testing
-        
-        require!(
-            is_professional_period || is_development_period,
-            StakeError::InvalidLockPeriod
-        );
+        // Validate lock duration against the tier table (dev-lock-periods builds also allow testing periods)
+        let (monthly_weight_bps, completion_multiplier_bps) =
+            ctx.accounts.staking_config.lock_terms(lock_duration_seconds)?;
 
         // Initialize user counter if this is the first time
         if user_stake_counter.user == Pubkey::default() {
@@ -375,23 +1033,57 @@ pub mod staker {
             StakeError::InvalidStakeIndex
         );
 
-        // Transfer full RR3 amount from user to program's staking bag (no commission deducted from RR3)
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.user_rr3_token_bag.to_account_info(),
-                authority: ctx.accounts.user_rr3_token_bag_authority.to_account_info(),
-                to: ctx.accounts.program_rr3_token_bag.to_account_info(),
-            }
+        require!(rr3_amount >= ctx.accounts.pool.min_stake_amount, StakeError::BelowMinimumStake);
+
+        // Calculate RR3 commission (3.33% of staked amount goes to the commission wallets)
+        let commission_amount = staking_commission(rr3_amount);
+
+        // Transfer net RR3 amount from user to program's staking bag
+        // With a Token-2022 transfer fee the stake is what actually arrived in the bag
+        let net_stake_amount = transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_rr3_token_bag,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.user_rr3_token_bag_authority,
+            &ctx.accounts.principal_vault,
+            rr3_amount - commission_amount,
+        )?;
+        require!(net_stake_amount > 0, StakeError::InvalidStakeAmount);
+
+
+        // Route the commission to the fee, expense and marketing wallets
+        let (fee_amount, expense_amount, marketing_amount) = transfer_commission(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_rr3_token_bag,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.user_rr3_token_bag_authority,
+            [
+                &ctx.accounts.fee_wallet_token_bag,
+                &ctx.accounts.expense_wallet_token_bag,
+                &ctx.accounts.marketing_wallet_token_bag,
+            ],
+            commission_amount,
+        )?;
+        distribution_state.record_commission(fee_amount, expense_amount, marketing_amount);
+
+        emit!(CommissionRouted {
+            pool: ctx.accounts.pool.key(),
+            user: ctx.accounts.user_rr3_token_bag_authority.key(),
+            stake_index,
+            fee_amount,
+            expense_amount,
+            marketing_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        let decimals = ctx.accounts.pool.decimals;
+        msg!("RR3 commission: {} tokens (3.33%)", format_ui_amount(commission_amount, decimals));
+        msg!("Fee wallet: {}, Expense wallet: {}, Marketing wallet: {}",
+            format_ui_amount(fee_amount, decimals),
+            format_ui_amount(expense_amount, decimals),
+            format_ui_amount(marketing_amount, decimals)
         );
-        token::transfer(cpi_ctx, rr3_amount)?;
-
-        // Calculate RR3 commission (3.33% of staked amount stays in program treasury)
-        let commission_amount = (rr3_amount * TOTAL_COMMISSION_BPS) / 10000;
-        let net_stake_amount = rr3_amount - commission_amount;
-        
-        msg!("RR3 commission: {} tokens (3.33%)", commission_amount);
-        msg!("Net stake amount: {} RR3 tokens", net_stake_amount);
+        msg!("Net stake amount: {} RR3 tokens", format_ui_amount(net_stake_amount, decimals));
 
         // Record the stake (net amount after commission)
         stake_record.user = ctx.accounts.user_rr3_token_bag_authority.key();
@@ -403,64 +1095,209 @@ pub mod staker {
         stake_record.last_reward_claim = clock.unix_timestamp;
         stake_record.pending_rr3_rewards = 0;
         stake_record.total_rr3_claimed = 0;
-        stake_record.last_distribution_round = 0;
+        stake_record.last_distribution_round = distribution_state.distribution_round;
+        stake_record.monthly_weight_bps = monthly_weight_bps;
+        stake_record.completion_multiplier_bps = completion_multiplier_bps;
 
         // Update user counter
         user_stake_counter.total_stakes += 1;
         user_stake_counter.active_stakes += 1;
 
+        // Add the weighted stake to the global reward denominator
+        let weighted_amount = weighted_stake_amount(net_stake_amount, monthly_weight_bps);
+        distribution_state.total_rr3_staked = distribution_state.total_rr3_staked
+            .checked_add(weighted_amount).unwrap();
+
+        // The principal vault must keep backing the principal of every active stake
+        distribution_state.total_principal = distribution_state.total_principal
+            .checked_add(net_stake_amount).unwrap();
+        require_principal_backed(&mut ctx.accounts.principal_vault, distribution_state.total_principal)?;
+
+        // Only rounds recorded from now on accrue to this stake
+        stake_record.reward_debt = accrued_rewards(weighted_amount, distribution_state.acc_reward_per_weighted_share);
+
+        // Reserve the worst-case completion bonus; reject the stake if the bonus reserve can't cover it
+        let bonus_liability = completion_bonus(net_stake_amount, completion_multiplier_bps);
+        distribution_state.total_bonus_liability = distribution_state.total_bonus_liability
+            .checked_add(bonus_liability).unwrap();
+        require!(
+            ctx.accounts.bonus_reserve_vault.amount >= distribution_state.total_bonus_liability,
+            StakeError::InsufficientBonusReserve
+        );
+
+        emit!(StakeCreated {
+            pool: ctx.accounts.pool.key(),
+            user: stake_record.user,
+            stake_index,
+            gross_amount: rr3_amount,
+            commission_amount,
+            net_amount: net_stake_amount,
+            lock_duration: lock_duration_seconds,
+            unlock_time: stake_record.unlock_time,
+            monthly_weight_bps,
+            completion_multiplier_bps,
+            round: distribution_state.distribution_round,
+            timestamp: clock.unix_timestamp,
+        });
+
         msg!("Stake #{} recorded successfully. Unlocks at: {}", stake_index, stake_record.unlock_time);
         Ok(())
     }
 
-    // Add to existing stake
+    // Top up an existing stake (same commission as stake)
+    // WeightedAverage keeps the stake's tier and moves unlock time to the amount-weighted average (locked stakes only)
+    // RestartLock restarts the whole stake under the given lock duration from now
     pub fn add_stake(
         ctx: Context<AddStake>,
-        _program_rr3_bag_bump: u8,
+        stake_index: u32,
         rr3_amount: u64,
-        lock_duration_seconds: i64,
+        top_up_mode: TopUpMode,
     ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_STAKING)?;
+
         let stake_record = &mut ctx.accounts.stake_record;
+        let distribution_state = &mut ctx.accounts.distribution_state;
         let clock = Clock::get()?;
 
-        msg!("Adding {} RR3 tokens to existing stake with {} seconds lock", rr3_amount, lock_duration_seconds);
+        msg!("Adding {} RR3 tokens to stake #{}", format_ui_amount(rr3_amount, ctx.accounts.pool.decimals), stake_index);
 
-        // Validate lock duration - allow development testing periods
-        let is_professional_period = lock_duration_seconds == LOCK_3_MONTHS ||
-            lock_duration_seconds == LOCK_6_MONTHS ||
-            lock_duration_seconds == LOCK_1_YEAR ||
-            lock_duration_seconds == LOCK_2_YEARS ||
-            lock_duration_seconds == LOCK_3_YEARS;
-        
-        let is_development_period = lock_duration_seconds >= 300; // Allow 5+ minutes for testing
-        
-        require!(
-            is_professional_period || is_development_period,
-            StakeError::InvalidLockPeriod
+        // Validate lock duration against the tier table - retired tiers accept no new tokens
+        let lock_duration_seconds = match top_up_mode {
+            TopUpMode::WeightedAverage => stake_record.lock_duration,
+            TopUpMode::RestartLock { lock_duration_seconds } => lock_duration_seconds,
+        };
+        let (monthly_weight_bps, completion_multiplier_bps) =
+            ctx.accounts.staking_config.lock_terms(lock_duration_seconds)?;
+
+        // Calculate RR3 commission (3.33% of added amount goes to the commission wallets)
+        let commission_amount = staking_commission(rr3_amount);
+
+        // Transfer net RR3 amount from user to program's staking bag
+        // With a Token-2022 transfer fee the top-up is what actually arrived in the bag
+        let net_added_amount = transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_rr3_token_bag,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.user_rr3_token_bag_authority,
+            &ctx.accounts.principal_vault,
+            rr3_amount - commission_amount,
+        )?;
+        require!(net_added_amount > 0, StakeError::InvalidTopUpAmount);
+
+
+        // Route the commission to the fee, expense and marketing wallets
+        let (fee_amount, expense_amount, marketing_amount) = transfer_commission(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_rr3_token_bag,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.user_rr3_token_bag_authority,
+            [
+                &ctx.accounts.fee_wallet_token_bag,
+                &ctx.accounts.expense_wallet_token_bag,
+                &ctx.accounts.marketing_wallet_token_bag,
+            ],
+            commission_amount,
+        )?;
+        distribution_state.record_commission(fee_amount, expense_amount, marketing_amount);
+
+        emit!(CommissionRouted {
+            pool: ctx.accounts.pool.key(),
+            user: ctx.accounts.user_rr3_token_bag_authority.key(),
+            stake_index,
+            fee_amount,
+            expense_amount,
+            marketing_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        let decimals = ctx.accounts.pool.decimals;
+        msg!("RR3 commission: {} tokens (3.33%)", format_ui_amount(commission_amount, decimals));
+        msg!("Fee wallet: {}, Expense wallet: {}, Marketing wallet: {}",
+            format_ui_amount(fee_amount, decimals),
+            format_ui_amount(expense_amount, decimals),
+            format_ui_amount(marketing_amount, decimals)
         );
 
-        // Transfer RR3 from user to program's staking bag
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.user_rr3_token_bag.to_account_info(),
-                authority: ctx.accounts.user_rr3_token_bag_authority.to_account_info(),
-                to: ctx.accounts.program_rr3_token_bag.to_account_info(),
+        // Settle rewards earned at the old weight before changing it
+        settle_stake_rewards(stake_record, distribution_state.acc_reward_per_weighted_share);
+
+        // Weight and bonus liability before the top-up, replaced in the global totals below
+        let old_weighted_amount = weighted_stake_amount(stake_record.amount, stake_record.monthly_weight_bps);
+        let old_bonus_liability = completion_bonus(stake_record.amount, stake_record.completion_multiplier_bps);
+
+        let old_amount = stake_record.amount;
+        let new_amount = old_amount.checked_add(net_added_amount).unwrap();
+
+        match top_up_mode {
+            TopUpMode::WeightedAverage => {
+                // Stake time and unlock time move to the amount-weighted average of old and new tokens,
+                // so the lock length and tier terms of the stake are unchanged
+                let (stake_time, unlock_time) = weighted_top_up_times(
+                    stake_record.stake_time,
+                    stake_record.unlock_time,
+                    stake_record.lock_duration,
+                    old_amount,
+                    net_added_amount,
+                    clock.unix_timestamp,
+                )?;
+                stake_record.stake_time = stake_time;
+                stake_record.unlock_time = unlock_time;
+            }
+            TopUpMode::RestartLock { lock_duration_seconds } => {
+                // Whole stake restarts under the chosen tier's current terms, never unlocking earlier than before
+                let unlock_time = restart_unlock_time(stake_record.unlock_time, lock_duration_seconds, clock.unix_timestamp)?;
+                stake_record.stake_time = clock.unix_timestamp;
+                stake_record.lock_duration = lock_duration_seconds;
+                stake_record.unlock_time = unlock_time;
+                stake_record.monthly_weight_bps = monthly_weight_bps;
+                stake_record.completion_multiplier_bps = completion_multiplier_bps;
             }
+        }
+        stake_record.amount = new_amount;
+
+        // Replace the old weight with the new one in the global reward denominator
+        let new_weighted_amount = weighted_stake_amount(stake_record.amount, stake_record.monthly_weight_bps);
+        distribution_state.total_rr3_staked = distribution_state.total_rr3_staked
+            .saturating_sub(old_weighted_amount)
+            .checked_add(new_weighted_amount).unwrap();
+
+        // The principal vault must keep backing the principal of every active stake
+        distribution_state.total_principal = distribution_state.total_principal
+            .checked_add(net_added_amount).unwrap();
+        require_principal_backed(&mut ctx.accounts.principal_vault, distribution_state.total_principal)?;
+        stake_record.reward_debt = accrued_rewards(new_weighted_amount, distribution_state.acc_reward_per_weighted_share);
+
+        // Re-reserve the completion bonus at the new amount and terms
+        let new_bonus_liability = completion_bonus(stake_record.amount, stake_record.completion_multiplier_bps);
+        distribution_state.total_bonus_liability = distribution_state.total_bonus_liability
+            .saturating_sub(old_bonus_liability)
+            .checked_add(new_bonus_liability).unwrap();
+        require!(
+            ctx.accounts.bonus_reserve_vault.amount >= distribution_state.total_bonus_liability,
+            StakeError::InsufficientBonusReserve
         );
-        token::transfer(cpi_ctx, rr3_amount)?;
-
-        // Add to existing stake and extend lock duration if longer
-        stake_record.amount += rr3_amount;
         
-        // If new lock duration is longer, extend the unlock time
-        let new_unlock_time = clock.unix_timestamp + lock_duration_seconds;
-        if new_unlock_time > stake_record.unlock_time {
-            stake_record.lock_duration = lock_duration_seconds;
-            stake_record.unlock_time = new_unlock_time;
-        }
+        emit!(StakeToppedUp {
+            pool: ctx.accounts.pool.key(),
+            user: stake_record.user,
+            stake_index,
+            gross_amount: rr3_amount,
+            commission_amount,
+            net_amount: net_added_amount,
+            new_total_amount: stake_record.amount,
+            restarted_lock: matches!(top_up_mode, TopUpMode::RestartLock { .. }),
+            lock_duration: stake_record.lock_duration,
+            unlock_time: stake_record.unlock_time,
+            round: distribution_state.distribution_round,
+            timestamp: clock.unix_timestamp,
+        });
         
-        msg!("Added {} RR3 to existing stake. New total: {}", rr3_amount, stake_record.amount);
+        msg!("Added {} RR3 to stake #{}. New total: {}. Unlocks at: {}",
+            format_ui_amount(net_added_amount, decimals),
+            stake_index,
+            format_ui_amount(stake_record.amount, decimals),
+            stake_record.unlock_time
+        );
         Ok(())
     }
 
@@ -470,53 +1307,69 @@ pub mod staker {
     pub fn claim_rr3_rewards(
         ctx: Context<ClaimRR3Rewards>,
         stake_index: u32,
-        _program_rr3_bag_bump: u8,
     ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_CLAIMING)?;
+
         let stake_record = &mut ctx.accounts.stake_record;
         
         msg!("Claiming RR3 rewards for stake #{}", stake_index);
 
+        settle_stake_rewards(stake_record, ctx.accounts.distribution_state.acc_reward_per_weighted_share);
+
         let pending_rewards = stake_record.pending_rr3_rewards;
         require!(pending_rewards > 0, StakeError::NoRewardsAvailable);
+        require!(
+            ctx.accounts.rewards_vault.amount >= pending_rewards,
+            StakeError::InsufficientTreasuryFunds
+        );
 
         // Transfer RR3 tokens from program treasury to user
-        let seeds = &[
-            b"token_bag".as_ref(),
-            &[_program_rr3_bag_bump],
-        ];
-        let signer = &[&seeds[..]];
-        
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.program_rr3_token_bag.to_account_info(),
-                authority: ctx.accounts.program_rr3_token_bag.to_account_info(),
-                to: ctx.accounts.user_rr3_token_bag.to_account_info(),
-            },
-            signer
-        );
-        token::transfer(cpi_ctx, pending_rewards)?;
+        let received_rewards = transfer_from_pool_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            PoolVault::Rewards,
+            &ctx.accounts.rewards_vault,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.user_rr3_token_bag,
+            pending_rewards,
+        )?;
 
         // Update stake record
         stake_record.pending_rr3_rewards = 0;
         stake_record.total_rr3_claimed += pending_rewards;
         stake_record.last_reward_claim = Clock::get()?.unix_timestamp;
+        ctx.accounts.distribution_state.release_rewards(pending_rewards);
+
+        emit!(RewardsClaimed {
+            pool: ctx.accounts.pool.key(),
+            user: stake_record.user,
+            stake_index,
+            amount: received_rewards,
+            round: ctx.accounts.distribution_state.distribution_round,
+            timestamp: stake_record.last_reward_claim,
+        });
         
-        msg!("Claimed {} RR3 as rewards", pending_rewards as f64 / 100_000_000.0);
+        msg!("Claimed {} RR3 as rewards", format_ui_amount(received_rewards, ctx.accounts.pool.decimals));
         Ok(())
     }
 
     // Unstake principal + any pending RR3 rewards  
     pub fn unstake(
         ctx: Context<UnStake>,
-        program_rr3_bag_bump: u8,
         stake_index: u32,
     ) -> Result<()> {
-        let stake_record = &ctx.accounts.stake_record;
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_UNSTAKING)?;
+
+        let decimals = ctx.accounts.pool.decimals;
+        let stake_record = &mut ctx.accounts.stake_record;
         let user_stake_counter = &mut ctx.accounts.user_stake_counter;
+        let distribution_state = &mut ctx.accounts.distribution_state;
         let clock = Clock::get()?;
 
-        msg!("Unstaking stake #{} with {} RR3 tokens", stake_index, stake_record.amount);
+        msg!("Unstaking stake #{} with {} RR3 tokens", stake_index, format_ui_amount(stake_record.amount, decimals));
+
+        // Settle any rewards accrued since the last claim
+        settle_stake_rewards(stake_record, distribution_state.acc_reward_per_weighted_share);
 
         let pending_rr3_rewards = stake_record.pending_rr3_rewards;
         let is_fully_unlocked = clock.unix_timestamp >= stake_record.unlock_time;
@@ -525,26 +1378,8 @@ pub mod staker {
         let (final_bonus, monthly_rewards_to_return): (u64, u64) = if is_fully_unlocked {
             // NORMAL UNLOCK: Full completion after lock period ends
             // User gets: principal + completion bonus + all monthly rewards earned
-            let final_multiplier_bps: u64 = match stake_record.lock_duration {
-                LOCK_3_MONTHS => REWARD_MULTIPLIER_3_MONTHS,
-                LOCK_6_MONTHS => REWARD_MULTIPLIER_6_MONTHS,
-                LOCK_1_YEAR => REWARD_MULTIPLIER_1_YEAR,
-                LOCK_2_YEARS => REWARD_MULTIPLIER_2_YEARS,
-                LOCK_3_YEARS => REWARD_MULTIPLIER_3_YEARS,
-                _ => {
-                    // For non-standard lock periods, no completion bonus
-                    // Only professional lock periods (3mo, 6mo, 1yr, 2yr, 3yr) get bonuses
-                    100 // 1.0x - return only principal, no bonus
-                }
-            };
-
-            // final payout = principal * multiplier / 100
-            let final_payout = ((stake_record.amount as u128)
-                .checked_mul(final_multiplier_bps as u128).unwrap()
-                .checked_div(100).unwrap()) as u64;
-
-            // bonus = final_payout - principal
-            let completion_bonus = final_payout.saturating_sub(stake_record.amount);
+            // Completion multiplier fixed from the lock tier when the stake was created
+            let completion_bonus = completion_bonus(stake_record.amount, stake_record.completion_multiplier_bps);
             
             (completion_bonus, pending_rr3_rewards)
         } else {
@@ -557,7 +1392,7 @@ pub mod staker {
             //   ❌ Forfeits completion bonus (didn't complete full lock period)
             msg!("Emergency Unlock activated - returning principal + earned monthly rewards");
             msg!("Returning: {} RR3 in earned monthly rewards", 
-                pending_rr3_rewards as f64 / 100_000_000.0
+                format_ui_amount(pending_rr3_rewards, decimals)
             );
             msg!("Forfeited: Completion bonus only (requires full lock period)");
             
@@ -569,46 +1404,364 @@ pub mod staker {
             .saturating_add(final_bonus)              // Completion bonus (only if fully unlocked)
             .saturating_add(monthly_rewards_to_return); // Monthly rewards (only if fully unlocked)
 
-        // Transfer principal + rewards back to user from staking bag
-        let rr3_mint_address = ctx.accounts.rr3_mint.key();
-        let seeds = &[rr3_mint_address.as_ref(), &[program_rr3_bag_bump]];
-        let signer = [&seeds[..]];
+        // Each payout comes from its own vault so no payout dips into other stakers' principal
+        require!(
+            ctx.accounts.bonus_reserve_vault.amount >= final_bonus &&
+                ctx.accounts.rewards_vault.amount >= monthly_rewards_to_return,
+            StakeError::InsufficientTreasuryFunds
+        );
 
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.program_rr3_token_bag.to_account_info(),
-                authority: ctx.accounts.program_rr3_token_bag.to_account_info(),
-                to: ctx.accounts.user_rr3_token_bag.to_account_info()
-            },
-            &signer
-        );
-        token::transfer(cpi_ctx, total_rr3_to_return)?;
+        // Transfer principal back to user from the principal vault
+        // Amounts received by the user can be lower than paid out with a Token-2022 transfer fee
+        let received_principal = transfer_from_pool_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            PoolVault::Principal,
+            &ctx.accounts.principal_vault,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.user_rr3_token_bag,
+            stake_record.amount,
+        )?;
+
+        // Transfer completion bonus from the bonus reserve
+        let mut received_bonus = 0;
+        if final_bonus > 0 {
+            received_bonus = transfer_from_pool_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.pool,
+                PoolVault::BonusReserve,
+                &ctx.accounts.bonus_reserve_vault,
+                &ctx.accounts.rr3_mint,
+                &ctx.accounts.user_rr3_token_bag,
+                final_bonus,
+            )?;
+        }
+
+        // Transfer monthly rewards from the rewards treasury
+        let mut received_rewards = 0;
+        if monthly_rewards_to_return > 0 {
+            received_rewards = transfer_from_pool_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.pool,
+                PoolVault::Rewards,
+                &ctx.accounts.rewards_vault,
+                &ctx.accounts.rr3_mint,
+                &ctx.accounts.user_rr3_token_bag,
+                monthly_rewards_to_return,
+            )?;
+        }
+        distribution_state.release_rewards(monthly_rewards_to_return);
+
+        // Release this stake's reserved completion bonus (paid above or forfeited)
+        distribution_state.total_bonus_liability = distribution_state.total_bonus_liability
+            .saturating_sub(completion_bonus(stake_record.amount, stake_record.completion_multiplier_bps));
 
         // Update user stake counter
         user_stake_counter.active_stakes = user_stake_counter.active_stakes.saturating_sub(1);
 
+        // Remove the weighted stake from the global reward denominator
+        let weighted_amount = weighted_stake_amount(stake_record.amount, stake_record.monthly_weight_bps);
+        distribution_state.total_rr3_staked = distribution_state.total_rr3_staked.saturating_sub(weighted_amount);
+
+        // Only this stake's principal may have left the principal vault
+        distribution_state.total_principal = distribution_state.total_principal.saturating_sub(stake_record.amount);
+        require_principal_backed(&mut ctx.accounts.principal_vault, distribution_state.total_principal)?;
+
+        emit!(Unstaked {
+            pool: ctx.accounts.pool.key(),
+            user: stake_record.user,
+            stake_index,
+            principal: received_principal,
+            bonus: received_bonus,
+            rewards: received_rewards,
+            early: !is_fully_unlocked,
+            round: distribution_state.distribution_round,
+            timestamp: clock.unix_timestamp,
+        });
+
         if is_fully_unlocked {
             msg!("✓ Normal Unlock - Stake #{} completed after full lock period", stake_index);
-            msg!("  Principal: {} RR3", stake_record.amount as f64 / 100_000_000.0);
-            msg!("  Completion bonus: {} RR3", final_bonus as f64 / 100_000_000.0);
-            msg!("  Monthly rewards: {} RR3", monthly_rewards_to_return as f64 / 100_000_000.0);
-            msg!("  TOTAL PAYOUT: {} RR3", total_rr3_to_return as f64 / 100_000_000.0);
+            msg!("  Principal: {} RR3", format_ui_amount(stake_record.amount, decimals));
+            msg!("  Completion bonus: {} RR3", format_ui_amount(final_bonus, decimals));
+            msg!("  Monthly rewards: {} RR3", format_ui_amount(monthly_rewards_to_return, decimals));
+            msg!("  TOTAL PAYOUT: {} RR3", format_ui_amount(total_rr3_to_return, decimals));
         } else {
             msg!("⚠ Emergency Unlock - Stake #{} exited early (before lock period completion)", stake_index);
-            msg!("  Net principal returned: {} RR3", stake_record.amount as f64 / 100_000_000.0);
-            msg!("  Earned monthly rewards returned: {} RR3", monthly_rewards_to_return as f64 / 100_000_000.0);
+            msg!("  Net principal returned: {} RR3", format_ui_amount(stake_record.amount, decimals));
+            msg!("  Earned monthly rewards returned: {} RR3", format_ui_amount(monthly_rewards_to_return, decimals));
             msg!("  Forfeited completion bonus: 0 RR3 (requires full lock period)");
-            msg!("  TOTAL PAYOUT: {} RR3 (principal + earned monthly rewards)", total_rr3_to_return as f64 / 100_000_000.0);
+            msg!("  TOTAL PAYOUT: {} RR3 (principal + earned monthly rewards)", format_ui_amount(total_rr3_to_return, decimals));
         }
         Ok(())
     }
 
-    // Check if a staker is eligible for monthly rewards with weighted multiplier info
-    pub fn check_eligibility(
-        ctx: Context<CheckEligibility>,
+    // Emergency principal-only withdrawal - never paused, so users are never trapped
+    // Returns the net staked principal only; pending rewards and the completion bonus are forfeited
+    pub fn emergency_withdraw(
+        ctx: Context<EmergencyWithdraw>,
+        stake_index: u32,
+    ) -> Result<()> {
+        let decimals = ctx.accounts.pool.decimals;
+        let stake_record = &ctx.accounts.stake_record;
+        let user_stake_counter = &mut ctx.accounts.user_stake_counter;
+        let distribution_state = &mut ctx.accounts.distribution_state;
+        let clock = Clock::get()?;
+
+        msg!("Emergency withdrawal of stake #{} with {} RR3 tokens", stake_index, format_ui_amount(stake_record.amount, decimals));
+
+        // Transfer principal back to user from the principal vault
+        let received_principal = transfer_from_pool_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            PoolVault::Principal,
+            &ctx.accounts.principal_vault,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.user_rr3_token_bag,
+            stake_record.amount,
+        )?;
+
+        // Forfeited rewards = settled pending rewards + anything accrued since the last settlement
+        let weighted_amount = weighted_stake_amount(stake_record.amount, stake_record.monthly_weight_bps);
+        let forfeited_rewards = stake_record.pending_rr3_rewards.saturating_add(
+            accrued_rewards(weighted_amount, distribution_state.acc_reward_per_weighted_share)
+                .saturating_sub(stake_record.reward_debt) as u64
+        );
+
+        // Release the stake from the global totals
+        distribution_state.release_rewards(forfeited_rewards);
+        // Forfeited rewards roll into the next recorded round, or go to the carryover sink
+        roll_over_rewards(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            &ctx.accounts.rewards_vault,
+            &ctx.accounts.rr3_mint,
+            ctx.accounts.carryover_sink_token_bag.as_deref(),
+            distribution_state,
+            forfeited_rewards,
+        )?;
+        distribution_state.total_bonus_liability = distribution_state.total_bonus_liability
+            .saturating_sub(completion_bonus(stake_record.amount, stake_record.completion_multiplier_bps));
+        distribution_state.total_rr3_staked = distribution_state.total_rr3_staked.saturating_sub(weighted_amount);
+        distribution_state.total_principal = distribution_state.total_principal.saturating_sub(stake_record.amount);
+        user_stake_counter.active_stakes = user_stake_counter.active_stakes.saturating_sub(1);
+
+        // Only this stake's principal may have left the principal vault
+        require_principal_backed(&mut ctx.accounts.principal_vault, distribution_state.total_principal)?;
+
+        emit!(EmergencyWithdrawn {
+            pool: ctx.accounts.pool.key(),
+            user: stake_record.user,
+            stake_index,
+            principal: received_principal,
+            forfeited_rewards,
+            round: distribution_state.distribution_round,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Emergency withdrawal complete - principal returned: {} RR3, forfeited rewards: {} RR3",
+            format_ui_amount(received_principal, decimals),
+            format_ui_amount(forfeited_rewards, decimals)
+        );
+        Ok(())
+    }
+
+    // Emergency buyback: sell a locked stake to the buyback treasury at a discount to principal
+    // The principal moves from the staking bag into the buyback treasury, the user is paid
+    // principal minus the configured discount; unclaimed rewards and the completion bonus are forfeited
+    pub fn emergency_buyback(
+        ctx: Context<EmergencyBuyback>,
         stake_index: u32,
     ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_UNSTAKING)?;
+
+        let decimals = ctx.accounts.pool.decimals;
+        let stake_record = &ctx.accounts.stake_record;
+        let user_stake_counter = &mut ctx.accounts.user_stake_counter;
+        let distribution_state = &mut ctx.accounts.distribution_state;
+        let clock = Clock::get()?;
+
+        // Only locked stakes can be sold - a matured stake unstakes with its bonus and rewards instead
+        require!(clock.unix_timestamp < stake_record.unlock_time, StakeError::BuybackOfUnlockedStake);
+
+        let principal = stake_record.amount;
+        let buyback_discount_bps = ctx.accounts.staking_config.buyback_discount_bps;
+        let buyback_payout = ((principal as u128)
+            .checked_mul((BPS_DENOMINATOR - buyback_discount_bps) as u128).unwrap()
+            .checked_div(BPS_DENOMINATOR as u128).unwrap()) as u64;
+
+        msg!("Emergency buyback of stake #{} with {} RR3 tokens", stake_index, format_ui_amount(principal, decimals));
+
+        // Move the forfeited principal from the staking bag into the buyback treasury
+        let pool_key = ctx.accounts.pool.key();
+        let received_principal = transfer_from_pool_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            PoolVault::Principal,
+            &ctx.accounts.principal_vault,
+            &ctx.accounts.rr3_mint,
+            &ctx.accounts.buyback_treasury_bag,
+            principal,
+        )?;
+
+        // Pay the user from the buyback treasury at the configured discount
+        ctx.accounts.buyback_treasury_bag.reload()?;
+        require!(
+            ctx.accounts.buyback_treasury_bag.amount >= buyback_payout,
+            StakeError::InsufficientTreasuryFunds
+        );
+        let received_payout = transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyback_treasury_bag,
+            &ctx.accounts.rr3_mint,
+            &[b"buyback_treasury".as_ref(), pool_key.as_ref(), &[ctx.bumps.buyback_treasury_bag]],
+            &ctx.accounts.user_rr3_token_bag,
+            buyback_payout,
+        )?;
+
+        // Forfeited rewards = settled pending rewards + anything accrued since the last settlement
+        let weighted_amount = weighted_stake_amount(principal, stake_record.monthly_weight_bps);
+        let forfeited_rewards = stake_record.pending_rr3_rewards.saturating_add(
+            accrued_rewards(weighted_amount, distribution_state.acc_reward_per_weighted_share)
+                .saturating_sub(stake_record.reward_debt) as u64
+        );
+
+        // Release the stake from the global totals and record the buyback
+        distribution_state.release_rewards(forfeited_rewards);
+        // Forfeited rewards roll into the next recorded round, or go to the carryover sink
+        roll_over_rewards(
+            &ctx.accounts.token_program,
+            &ctx.accounts.pool,
+            &ctx.accounts.rewards_vault,
+            &ctx.accounts.rr3_mint,
+            ctx.accounts.carryover_sink_token_bag.as_deref(),
+            distribution_state,
+            forfeited_rewards,
+        )?;
+        distribution_state.total_bonus_liability = distribution_state.total_bonus_liability
+            .saturating_sub(completion_bonus(principal, stake_record.completion_multiplier_bps));
+        distribution_state.total_rr3_staked = distribution_state.total_rr3_staked.saturating_sub(weighted_amount);
+        distribution_state.total_principal = distribution_state.total_principal.saturating_sub(principal);
+        require_principal_backed(&mut ctx.accounts.principal_vault, distribution_state.total_principal)?;
+        distribution_state.total_buyback_principal = distribution_state.total_buyback_principal
+            .checked_add(received_principal).unwrap();
+        distribution_state.total_buyback_paid = distribution_state.total_buyback_paid
+            .checked_add(buyback_payout).unwrap();
+        user_stake_counter.active_stakes = user_stake_counter.active_stakes.saturating_sub(1);
+
+        emit!(EmergencyBuybackExecuted {
+            pool: ctx.accounts.pool.key(),
+            user: stake_record.user,
+            stake_index,
+            principal,
+            payout: received_payout,
+            discount_bps: buyback_discount_bps,
+            forfeited_rewards,
+            round: distribution_state.distribution_round,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Emergency buyback complete - principal: {} RR3, paid: {} RR3 ({} bps discount)",
+            format_ui_amount(principal, decimals),
+            format_ui_amount(received_payout, decimals),
+            buyback_discount_bps
+        );
+        Ok(())
+    }
+
+    // User function: Burn RR3 from the user's own token account, reducing mint supply
+    // The cumulative burn is recorded per user and protocol-wide so supply reduction is provable on-chain
+    pub fn user_burn_tokens(
+        ctx: Context<UserBurnTokens>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, StakeError::InvalidBurnAmount);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::Burn {
+                mint: ctx.accounts.rr3_mint.to_account_info(),
+                from: ctx.accounts.user_rr3_token_bag.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+        );
+        token_interface::burn(cpi_ctx, amount)?;
+
+        let clock = Clock::get()?;
+        let user_burn_record = &mut ctx.accounts.user_burn_record;
+        let distribution_state = &mut ctx.accounts.distribution_state;
+
+        // Initialize burn record if this is the user's first burn
+        if user_burn_record.user == Pubkey::default() {
+            user_burn_record.user = ctx.accounts.user_authority.key();
+        }
+        user_burn_record.total_burned = user_burn_record.total_burned.checked_add(amount).unwrap();
+        user_burn_record.burn_count = user_burn_record.burn_count.checked_add(1).unwrap();
+        user_burn_record.last_burn_time = clock.unix_timestamp;
+
+        distribution_state.total_user_burned = distribution_state.total_user_burned
+            .checked_add(amount).unwrap();
+
+        emit!(UserTokensBurned {
+            pool: ctx.accounts.pool.key(),
+            user: user_burn_record.user,
+            amount,
+            user_total_burned: user_burn_record.total_burned,
+            protocol_total_burned: distribution_state.total_user_burned,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("User burned {} RR3 (lifetime: {} RR3)",
+            format_ui_amount(amount, ctx.accounts.pool.decimals),
+            format_ui_amount(user_burn_record.total_burned, ctx.accounts.pool.decimals)
+        );
+        Ok(())
+    }
+
+    // Treasurer function: Burn RR3 commission collected in the fee wallet (SPL burn, reducing mint supply)
+    pub fn burn_fees(
+        ctx: Context<BurnFees>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.distribution_state.require_not_paused(PAUSE_DISTRIBUTION)?;
+        require!(amount > 0, StakeError::InvalidBurnAmount);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::Burn {
+                mint: ctx.accounts.rr3_mint.to_account_info(),
+                from: ctx.accounts.fee_wallet_token_bag.to_account_info(),
+                authority: ctx.accounts.treasurer.to_account_info(),
+            },
+        );
+        token_interface::burn(cpi_ctx, amount)?;
+
+        let distribution_state = &mut ctx.accounts.distribution_state;
+        distribution_state.total_commission_burned = distribution_state.total_commission_burned
+            .checked_add(amount).unwrap();
+
+        emit!(FeesBurned {
+            pool: ctx.accounts.pool.key(),
+            fee_wallet: ctx.accounts.fee_wallet_token_bag.key(),
+            amount,
+            total_commission_burned: distribution_state.total_commission_burned,
+            round: distribution_state.distribution_round,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Burned {} RR3 from fee wallet (lifetime: {} RR3)",
+            format_ui_amount(amount, ctx.accounts.pool.decimals),
+            format_ui_amount(distribution_state.total_commission_burned, ctx.accounts.pool.decimals)
+        );
+        Ok(())
+    }
+
+    // View: Check if a staker is eligible for monthly rewards with weighted multiplier info
+    // Returns a StakeSummary through the Anchor return value (call via simulateTransaction)
+    pub fn check_eligibility(
+        ctx: Context<CheckEligibility>,
+        _stake_index: u32,
+    ) -> Result<StakeSummary> {
+        let decimals = ctx.accounts.pool.decimals;
         let stake_record = &ctx.accounts.stake_record;
         let clock = Clock::get()?;
         
@@ -617,37 +1770,470 @@ pub mod staker {
         let time_until_unlock = stake_record.unlock_time - clock.unix_timestamp;
         let is_unlocked = clock.unix_timestamp >= stake_record.unlock_time;
         
-        // Calculate reward multiplier
-        let lock_multiplier = match stake_record.lock_duration {
-            LOCK_3_MONTHS => 100,
-            LOCK_6_MONTHS => 101,
-            LOCK_1_YEAR => 102,
-            LOCK_2_YEARS => 103,
-            LOCK_3_YEARS => 103,
-            _ => {
-                let months = stake_record.lock_duration / (30 * 24 * 60 * 60);
-                if months == 0 { 50 } else { (100 + (months as u64 * 8)).min(100) }
-            }
-        };
-        
-        let weighted_amount = (stake_record.amount as u128)
-            .checked_mul(lock_multiplier as u128).unwrap()
-            .checked_div(100).unwrap();
+        // Reward multiplier fixed from the lock tier when the stake was created
+        let lock_multiplier = stake_record.monthly_weight_bps;
+        let weighted_amount = weighted_stake_amount(stake_record.amount, stake_record.monthly_weight_bps);
+        let tier_enabled = ctx.accounts.staking_config
+            .find_tier(stake_record.lock_duration)
+            .is_some_and(|tier| tier.enabled);
         
         msg!("Staker: {}", stake_record.user);
-        msg!("Staked amount: {} RR3", stake_record.amount as f64 / 100_000_000.0);
+        msg!("Staked amount: {} RR3", format_ui_amount(stake_record.amount, decimals));
         msg!("Weighted amount: {} RR3 ({}x multiplier)", 
-            weighted_amount as f64 / 100_000_000.0,
-            lock_multiplier as f64 / 100.0
+            format_ui_amount(weighted_amount, decimals),
+            format_bps_multiplier(lock_multiplier)
         );
+        msg!("Completion multiplier: {}x", format_bps_multiplier(stake_record.completion_multiplier_bps));
+        msg!("Lock tier still offered: {}", tier_enabled);
         msg!("Days staked: {}", days_staked);
         msg!("Lock duration: {} seconds", stake_record.lock_duration);
         msg!("Unlocked: {}", is_unlocked);
         msg!("Time until unlock: {} seconds", if is_unlocked { 0 } else { time_until_unlock });
-        msg!("Pending RR3 rewards: {} RR3", stake_record.pending_rr3_rewards as f64 / 100_000_000.0);
-        
-        Ok(())
+        let unsettled_rewards = (accrued_rewards(weighted_amount, ctx.accounts.distribution_state.acc_reward_per_weighted_share)
+            .saturating_sub(stake_record.reward_debt)) as u64;
+        let pending_rewards = stake_record.pending_rr3_rewards.saturating_add(unsettled_rewards);
+        msg!("Pending RR3 rewards: {} RR3", format_ui_amount(pending_rewards, decimals));
+
+        // Early exit (unstake before unlock) returns principal + earned rewards, forfeiting the bonus
+        let projected_completion_bonus = completion_bonus(stake_record.amount, stake_record.completion_multiplier_bps);
+        let early_exit_payout = stake_record.amount.saturating_add(pending_rewards);
+
+        Ok(StakeSummary {
+            user: stake_record.user,
+            stake_index: stake_record.stake_index,
+            amount: stake_record.amount,
+            weighted_amount,
+            monthly_weight_bps: stake_record.monthly_weight_bps,
+            completion_multiplier_bps: stake_record.completion_multiplier_bps,
+            lock_duration: stake_record.lock_duration,
+            unlock_time: stake_record.unlock_time,
+            seconds_to_unlock: time_until_unlock.max(0),
+            unlocked: is_unlocked,
+            tier_enabled,
+            pending_rewards,
+            projected_completion_bonus,
+            early_exit_payout,
+        })
+    }
+
+    // View: Full lock tier table, including retired tiers
+    pub fn get_lock_tiers(
+        ctx: Context<GetLockPeriodInfo>,
+    ) -> Result<Vec<LockTier>> {
+        Ok(ctx.accounts.staking_config.tiers.clone())
+    }
+
+    // View: Global pool statistics and vault balances
+    pub fn get_pool_stats(
+        ctx: Context<GetPoolStats>,
+    ) -> Result<PoolStats> {
+        let distribution_state = &ctx.accounts.distribution_state;
+
+        Ok(PoolStats {
+            pool: ctx.accounts.pool.key(),
+            mint: ctx.accounts.pool.mint,
+            decimals: ctx.accounts.pool.decimals,
+            min_stake_amount: ctx.accounts.pool.min_stake_amount,
+            total_weighted_staked: distribution_state.total_rr3_staked,
+            acc_reward_per_weighted_share: distribution_state.acc_reward_per_weighted_share,
+            total_bonus_liability: distribution_state.total_bonus_liability,
+            distribution_round: distribution_state.distribution_round,
+            last_distribution_time: distribution_state.last_distribution_time,
+            paused: distribution_state.paused,
+            total_principal: distribution_state.total_principal,
+            reward_liability: distribution_state.reward_liability,
+            principal_vault_balance: ctx.accounts.principal_vault.amount,
+            rewards_vault_balance: ctx.accounts.rewards_vault.amount,
+            bonus_reserve_vault_balance: ctx.accounts.bonus_reserve_vault.amount,
+            total_buyback_principal: distribution_state.total_buyback_principal,
+            total_buyback_paid: distribution_state.total_buyback_paid,
+            total_user_burned: distribution_state.total_user_burned,
+        })
+    }
+}
+
+// Upgrade authority recorded in the program's ProgramData account (None once the program is immutable)
+pub fn program_upgrade_authority(program_data: &AccountInfo) -> Result<Option<Pubkey>> {
+    let data = program_data.try_borrow_data()?;
+    let program_data = ProgramData::try_deserialize(&mut &data[..])?;
+    Ok(program_data.upgrade_authority_address)
+}
+
+// Stake and unlock time of a WeightedAverage top-up: the amount-weighted average of the old stake time and now
+// Rejected once the stake is unlocked, so a top-up never unlocks its new tokens immediately
+pub fn weighted_top_up_times(
+    stake_time: i64,
+    unlock_time: i64,
+    lock_duration: i64,
+    old_amount: u64,
+    added_amount: u64,
+    now: i64,
+) -> Result<(i64, i64)> {
+    require!(now < unlock_time, StakeError::StakeAlreadyUnlocked);
+
+    let new_amount = old_amount.checked_add(added_amount).unwrap();
+    let new_stake_time = (((stake_time as i128) * (old_amount as i128)
+        + (now as i128) * (added_amount as i128))
+        / (new_amount as i128)) as i64;
+    Ok((new_stake_time, new_stake_time + lock_duration))
+}
+
+// Unlock time of a RestartLock top-up: now + lock_duration, rejected if it would shorten the current lock
+pub fn restart_unlock_time(unlock_time: i64, lock_duration: i64, now: i64) -> Result<i64> {
+    let new_unlock_time = now.checked_add(lock_duration).unwrap();
+    require!(new_unlock_time >= unlock_time, StakeError::LockShortened);
+    Ok(new_unlock_time)
+}
+
+// Weighted stake amount (amount * monthly_weight_bps / 10_000), the unit of DistributionState.total_rr3_staked
+pub fn weighted_stake_amount(amount: u64, monthly_weight_bps: u64) -> u64 {
+    (amount as u128)
+        .checked_mul(monthly_weight_bps as u128).unwrap()
+        .checked_div(BPS_DENOMINATOR as u128).unwrap() as u64
+}
+
+// Completion bonus on top of principal: principal * completion_multiplier_bps / 10_000 - principal
+pub fn completion_bonus(amount: u64, completion_multiplier_bps: u64) -> u64 {
+    let final_payout = (amount as u128)
+        .checked_mul(completion_multiplier_bps as u128).unwrap()
+        .checked_div(BPS_DENOMINATOR as u128).unwrap() as u64;
+    final_payout.saturating_sub(amount)
+}
+
+// Sanity checks shared by add_lock_tier and update_lock_tier
+pub fn validate_lock_tier(lock_duration: i64, monthly_weight_bps: u64, completion_multiplier_bps: u64) -> Result<()> {
+    require!(
+        lock_duration > 0 && monthly_weight_bps > 0 && completion_multiplier_bps >= BPS_DENOMINATOR,
+        StakeError::InvalidLockTier
+    );
+    Ok(())
+}
+
+// Staking commission on a gross amount (amount * TOTAL_COMMISSION_BPS / 10_000), computed in u128 so it never overflows
+pub fn staking_commission(amount: u64) -> u64 {
+    ((amount as u128) * (TOTAL_COMMISSION_BPS as u128) / (BPS_DENOMINATOR as u128)) as u64
+}
+
+// Split a commission between the fee, expense and marketing wallets (fee wallet takes rounding dust)
+pub fn split_commission(commission_amount: u64) -> (u64, u64, u64) {
+    let expense_amount = commission_amount * EXPENSE_WALLET_BPS / COMMISSION_SPLIT_TOTAL_BPS;
+    let marketing_amount = commission_amount * MARKETING_WALLET_BPS / COMMISSION_SPLIT_TOTAL_BPS;
+    let fee_amount = commission_amount - expense_amount - marketing_amount;
+    (fee_amount, expense_amount, marketing_amount)
+}
+
+// Amount that arrives when `amount` is transferred, net of any Token-2022 transfer fee on the mint
+pub fn amount_after_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let transfer_fee = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+        Err(_) => 0,
+    };
+    Ok(amount.checked_sub(transfer_fee).unwrap())
+}
+
+// User-signed transfer into a program vault, returns the amount that arrived
+pub fn transfer_to_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    authority: &Signer<'info>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+) -> Result<u64> {
+    let cpi_ctx = CpiContext::new(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: from.to_account_info(),
+            mint: mint.to_account_info(),
+            to: vault.to_account_info(),
+            authority: authority.to_account_info(),
+        }
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    amount_after_transfer_fee(mint, amount)
+}
+
+// Transfer a user's commission to the [fee, expense, marketing] wallets, returns the split
+pub fn transfer_commission<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    authority: &Signer<'info>,
+    wallets: [&InterfaceAccount<'info, TokenAccount>; 3],
+    commission_amount: u64,
+) -> Result<(u64, u64, u64)> {
+    let (fee_amount, expense_amount, marketing_amount) = split_commission(commission_amount);
+
+    for (wallet, amount) in wallets.into_iter().zip([fee_amount, expense_amount, marketing_amount]) {
+        if amount == 0 {
+            continue;
+        }
+        transfer_to_vault(token_program, from, mint, authority, wallet, amount)?;
+    }
+
+    Ok((fee_amount, expense_amount, marketing_amount))
+}
+
+// PDA-signed transfer out of a program vault whose authority is itself, returns the amount that arrived
+pub fn transfer_from_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    vault_seeds: &[&[u8]],
+    to: &InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+) -> Result<u64> {
+    let signer = &[vault_seeds];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: vault.to_account_info(),
+            mint: mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        signer
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    amount_after_transfer_fee(mint, amount)
+}
+
+// Roll rewards no stake will receive into the next recorded round, or send them to the configured carryover sink
+pub fn roll_over_rewards<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    pool: &Account<'info, Pool>,
+    rewards_vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    carryover_sink_token_bag: Option<&InterfaceAccount<'info, TokenAccount>>,
+    distribution_state: &mut DistributionState,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let destination = distribution_state.carryover_sink;
+    if destination == Pubkey::default() {
+        distribution_state.reward_carryover = distribution_state.reward_carryover
+            .checked_add(amount).unwrap();
+    } else {
+        let carryover_sink_token_bag = carryover_sink_token_bag.ok_or(StakeError::CarryoverSinkRequired)?;
+        transfer_from_pool_vault(
+            token_program,
+            pool,
+            PoolVault::Rewards,
+            rewards_vault,
+            mint,
+            carryover_sink_token_bag,
+            amount,
+        )?;
+    }
+
+    emit!(RoundLeftoverRolled {
+        pool: pool.key(),
+        round: distribution_state.distribution_round,
+        amount,
+        destination,
+        reward_carryover: distribution_state.reward_carryover,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// PDA-signed transfer out of one of a pool's vaults, signed with the bump stored on the pool
+pub fn transfer_from_pool_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    pool: &Account<'info, Pool>,
+    vault: PoolVault,
+    vault_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+) -> Result<u64> {
+    let pool_key = pool.key();
+    transfer_from_vault(
+        token_program,
+        vault_account,
+        mint,
+        &[vault.seed(), pool_key.as_ref(), &[pool.vault_bump(vault)]],
+        to,
+        amount,
+    )
+}
+
+// Principal invariant: the principal vault always holds at least the principal of every active stake
+pub fn require_principal_backed<'info>(
+    principal_vault: &mut InterfaceAccount<'info, TokenAccount>,
+    total_principal: u64,
+) -> Result<()> {
+    principal_vault.reload()?;
+    require!(
+        principal_vault.amount >= total_principal,
+        StakeError::PrincipalVaultUnderfunded
+    );
+    Ok(())
+}
+
+// Open the next accumulator round for total_rr3_for_rewards, which the rewards vault must already hold
+pub fn record_round(
+    pool: &Account<Pool>,
+    distribution_state: &mut DistributionState,
+    reward_round: &mut RewardRound,
+    previous_round: Option<&RewardRound>,
+    round_interval: i64,
+    rewards_vault_balance: u64,
+    total_rr3_for_rewards: u64,
+) -> Result<()> {
+    let decimals = pool.decimals;
+    let clock = Clock::get()?;
+    
+    // Rounds are at least round_interval apart and never overlap
+    distribution_state.require_round_interval_elapsed(round_interval, clock.unix_timestamp)?;
+    distribution_state.require_previous_round_closed(previous_round)?;
+
+    require!(
+        distribution_state.total_rr3_staked > 0,
+        StakeError::NoStakersForDistribution
+    );
+
+    // The whole allocation goes to stakers (commission is routed separately at stake time),
+    // plus the leftover of previously closed rounds
+    let carryover_amount = distribution_state.reward_carryover;
+    let round_reward_amount = total_rr3_for_rewards.checked_add(carryover_amount).unwrap();
+    distribution_state.reward_carryover = 0;
+
+    // The rewards vault must hold the recorded allocation and the carried-over leftover
+    // on top of what earlier rounds still owe
+    distribution_state.commit_rewards(round_reward_amount, rewards_vault_balance)?;
+
+    // Credit the round to every stake at once: each stake settles its share lazily
+    let acc_increment = (round_reward_amount as u128)
+        .checked_mul(ACC_REWARD_PRECISION).unwrap()
+        .checked_div(distribution_state.total_rr3_staked as u128).unwrap();
+    distribution_state.acc_reward_per_weighted_share = distribution_state.acc_reward_per_weighted_share
+        .checked_add(acc_increment).unwrap();
+
+    distribution_state.monthly_rr3_for_rewards = round_reward_amount;
+    distribution_state.distribution_round += 1;
+    distribution_state.last_distribution_time = clock.unix_timestamp; // Update timestamp for next check
+
+    reward_round.open(
+        distribution_state.distribution_round,
+        round_reward_amount,
+        distribution_state.total_rr3_staked,
+        acc_increment,
+        false,
+        clock.unix_timestamp,
+    );
+
+    emit!(RoundRecorded {
+        pool: pool.key(),
+        round: distribution_state.distribution_round,
+        total_rr3_for_rewards,
+        reward_amount: round_reward_amount,
+        carryover_amount,
+        total_weighted_staked: distribution_state.total_rr3_staked,
+        acc_reward_per_weighted_share: distribution_state.acc_reward_per_weighted_share,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Monthly RR3 rewards recorded: {} RR3 tokens total allocated", 
+        format_ui_amount(total_rr3_for_rewards, decimals)
+    );
+    msg!("Credited to stakers: {} RR3 (carryover: {} RR3)",
+        format_ui_amount(round_reward_amount, decimals),
+        format_ui_amount(carryover_amount, decimals)
+    );
+    msg!("Distribution round: {}", distribution_state.distribution_round);
+    msg!("Accumulated reward per weighted share: {}", distribution_state.acc_reward_per_weighted_share);
+    
+    Ok(())
+}
+
+// Merkle leaf of one stake's reward; the leaf index is hashed in so a leaf can only be claimed at its own position
+pub fn merkle_leaf(leaf_index: u32, user: &Pubkey, stake_index: u32, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &[0u8],
+        &leaf_index.to_le_bytes(),
+        user.as_ref(),
+        &stake_index.to_le_bytes(),
+        &amount.to_le_bytes(),
+    ]).0
+}
+
+// Depth of a tree of leaf_count leaves padded to a power of two (ceil(log2(leaf_count)))
+pub fn merkle_depth(leaf_count: u32) -> usize {
+    (leaf_count as u64).next_power_of_two().trailing_zeros() as usize
+}
+
+// Verify a positional Merkle proof: bit i of leaf_index says whether the node is the right child at level i
+// Every proof has exactly merkle_depth(leaf_count) siblings, so trees are padded with zero leaves instead of promoting odd nodes
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32], leaf_index: u32, leaf_count: u32) -> bool {
+    if leaf_index >= leaf_count || proof.len() != merkle_depth(leaf_count) {
+        return false;
+    }
+    let mut node = leaf;
+    for (level, sibling) in proof.iter().enumerate() {
+        node = if (leaf_index >> level) & 1 == 0 {
+            keccak::hashv(&[&[1u8], &node, sibling]).0
+        } else {
+            keccak::hashv(&[&[1u8], sibling, &node]).0
+        };
+    }
+    node == root
+}
+
+// Format a raw token amount with the mint's decimals using integer arithmetic, e.g. 150_000_000 @ 8 => "1.5"
+pub fn format_ui_amount(amount: u64, decimals: u8) -> String {
+    // Beyond u128 the scale exceeds any u64 amount, so there is no whole part
+    let (whole, fraction) = match 10u128.checked_pow(decimals as u32) {
+        Some(scale) => (amount as u128 / scale, amount as u128 % scale),
+        None => (0, amount as u128),
+    };
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+// Whole tokens in raw units (tokens * 10^decimals), an error if that doesn't fit in a u64
+pub fn whole_tokens_to_raw(tokens: u64, decimals: u8) -> Result<u64> {
+    if tokens == 0 {
+        return Ok(0);
     }
+    10u64.checked_pow(decimals as u32)
+        .and_then(|scale| tokens.checked_mul(scale))
+        .ok_or_else(|| error!(StakeError::InvalidMinimumStake))
+}
+
+// Format a bps multiplier, e.g. 10_100 => "1.01"
+pub fn format_bps_multiplier(bps: u64) -> String {
+    format_ui_amount(bps, 4) // BPS_DENOMINATOR = 10^4
+}
+
+// Total rewards a weighted stake has accrued at the given accumulator value
+pub fn accrued_rewards(weighted_amount: u64, acc_reward_per_weighted_share: u128) -> u128 {
+    (weighted_amount as u128)
+        .checked_mul(acc_reward_per_weighted_share).unwrap()
+        .checked_div(ACC_REWARD_PRECISION).unwrap()
+}
+
+// Move rewards accrued since the last settlement into pending_rr3_rewards, returns the amount credited
+pub fn settle_stake_rewards(stake_record: &mut StakeRecord, acc_reward_per_weighted_share: u128) -> u64 {
+    let weighted_amount = weighted_stake_amount(stake_record.amount, stake_record.monthly_weight_bps);
+    let accrued = accrued_rewards(weighted_amount, acc_reward_per_weighted_share);
+    let owed = accrued.saturating_sub(stake_record.reward_debt) as u64;
+
+    stake_record.pending_rr3_rewards = stake_record.pending_rr3_rewards.checked_add(owed).unwrap();
+    stake_record.reward_debt = accrued;
+    owed
 }
 
 // Stake record account to track individual stakes
@@ -663,6 +2249,107 @@ pub struct StakeRecord {
     pub pending_rr3_rewards: u64, // 8 bytes - Claimable RR3 rewards (in RR3 token units)
     pub total_rr3_claimed: u64,   // 8 bytes - Total RR3 rewards claimed
     pub last_distribution_round: u64, // 8 bytes - Last distribution round participated in
+    pub reward_debt: u128,      // 16 bytes - Accrued rewards already settled (weighted * acc / precision)
+    pub monthly_weight_bps: u64, // 8 bytes - Monthly reward weight locked in from the tier at stake time
+    pub completion_multiplier_bps: u64, // 8 bytes - Completion multiplier locked in from the tier at stake time
+}
+
+// How add_stake applies a top-up to an existing stake's lock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum TopUpMode {
+    WeightedAverage,                            // Keep the tier, unlock time becomes the amount-weighted average
+    RestartLock { lock_duration_seconds: i64 }, // Restart the whole stake under this lock duration
+}
+
+// One entry of the lock tier table
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LockTier {
+    pub lock_duration: i64,             // 8 bytes - Lock duration in seconds
+    pub monthly_weight_bps: u64,        // 8 bytes - Share weight in the monthly reward pool (10_000 = 1.0x)
+    pub completion_multiplier_bps: u64, // 8 bytes - Final payout multiplier on principal (10_000 = 1.0x)
+    pub enabled: bool,                  // 1 byte - Retired tiers accept no new stakes
+}
+
+// Stake summary returned by check_eligibility
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StakeSummary {
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub amount: u64,
+    pub weighted_amount: u64,
+    pub monthly_weight_bps: u64,
+    pub completion_multiplier_bps: u64,
+    pub lock_duration: i64,
+    pub unlock_time: i64,
+    pub seconds_to_unlock: i64,      // 0 once unlocked
+    pub unlocked: bool,
+    pub tier_enabled: bool,
+    pub pending_rewards: u64,        // Settled + unsettled rewards
+    pub projected_completion_bonus: u64, // Paid on top of principal when unstaking after unlock
+    pub early_exit_payout: u64,      // Principal + pending rewards if unstaked now before unlock
+}
+
+// Pool statistics returned by get_pool_stats
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PoolStats {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub min_stake_amount: u64,
+    pub total_weighted_staked: u64,
+    pub acc_reward_per_weighted_share: u128,
+    pub total_bonus_liability: u64,
+    pub distribution_round: u64,
+    pub last_distribution_time: i64,
+    pub paused: u8,
+    pub total_principal: u64,
+    pub reward_liability: u64,
+    pub principal_vault_balance: u64,
+    pub rewards_vault_balance: u64,
+    pub bonus_reserve_vault_balance: u64,
+    pub total_buyback_principal: u64,
+    pub total_buyback_paid: u64,
+    pub total_user_burned: u64,
+}
+
+// Global lock tier table
+#[account]
+pub struct StakingConfig {
+    pub buyback_discount_bps: u64, // 8 bytes - Emergency buyback discount to principal
+    pub keeper_tip_per_stake: u64, // 8 bytes - crank_rewards tip per stake credited (raw units)
+    pub round_interval: i64,       // 8 bytes - Minimum seconds between distribution rounds
+    pub tiers: Vec<LockTier>,   // 4 + MAX_LOCK_TIERS * 25 bytes
+}
+
+impl StakingConfig {
+    pub fn find_tier(&self, lock_duration: i64) -> Option<&LockTier> {
+        self.tiers.iter().find(|tier| tier.lock_duration == lock_duration)
+    }
+
+    // Terms (monthly weight bps, completion multiplier bps) for a new stake or lock extension
+    pub fn lock_terms(&self, lock_duration: i64) -> Result<(u64, u64)> {
+        match self.find_tier(lock_duration) {
+            Some(tier) => {
+                require!(tier.enabled, StakeError::InvalidLockPeriod);
+                Ok((tier.monthly_weight_bps, tier.completion_multiplier_bps))
+            }
+            // Durations outside the tier table only exist in dev-lock-periods builds
+            #[cfg(not(feature = "dev-lock-periods"))]
+            None => err!(StakeError::InvalidLockPeriod),
+            #[cfg(feature = "dev-lock-periods")]
+            None => {
+                require!(lock_duration >= DEV_MIN_LOCK_DURATION, StakeError::InvalidLockPeriod);
+
+                // Development periods: 0.5x monthly weight under a month, 1.0x otherwise, no completion bonus
+                let monthly_weight_bps = if lock_duration < DEV_FULL_WEIGHT_LOCK_DURATION {
+                    5_000
+                } else {
+                    BPS_DENOMINATOR
+                };
+                Ok((monthly_weight_bps, BPS_DENOMINATOR))
+            }
+        }
+    }
 }
 
 // New: User stake counter to track number of stakes per user
@@ -674,60 +2361,698 @@ pub struct UserStakeCounter {
 }
 
 
+// Staking pool for a single mint - seeds [b"pool", mint]
+#[account]
+pub struct Pool {
+    pub mint: Pubkey,           // 32 bytes - Mint staked, rewarded and burned in this pool
+    pub decimals: u8,           // 1 byte - Mint decimals, read once at initialize_pool
+    pub min_stake_amount: u64,  // 8 bytes - Minimum gross stake in raw units (min_stake_tokens * 10^decimals)
+    pub created_at: i64,        // 8 bytes
+    pub bump: u8,               // 1 byte
+    pub principal_vault_bump: u8, // 1 byte - [b"staking_bag", pool], set by create_rr3_token_bag
+    pub rewards_vault_bump: u8,   // 1 byte - [b"token_bag", pool], set by create_treasury_bag
+    pub bonus_reserve_bump: u8,   // 1 byte - [b"bonus_reserve", pool], set by create_bonus_reserve
+}
+
+impl Pool {
+    pub fn vault_bump(&self, vault: PoolVault) -> u8 {
+        match vault {
+            PoolVault::Principal => self.principal_vault_bump,
+            PoolVault::Rewards => self.rewards_vault_bump,
+            PoolVault::BonusReserve => self.bonus_reserve_bump,
+        }
+    }
+}
+
+// The pool vaults: staked principal, round rewards and completion bonuses each have their own
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PoolVault {
+    Principal,    // Only ever pays a stake's principal back to its staker
+    Rewards,      // Monthly rewards and Merkle claims
+    BonusReserve, // Completion bonuses
+}
+
+impl PoolVault {
+    pub fn seed(self) -> &'static [u8] {
+        match self {
+            PoolVault::Principal => b"staking_bag",
+            PoolVault::Rewards => b"token_bag",
+            PoolVault::BonusReserve => b"bonus_reserve",
+        }
+    }
+}
+
+// Initial settings of a pool passed to initialize_pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PoolConfig {
+    pub distributor: Pubkey,
+    pub treasurer: Pubkey,
+    pub pauser: Pubkey,
+    pub buyback_discount_bps: Option<u64>, // None = DEFAULT_BUYBACK_DISCOUNT_BPS
+    pub min_stake_tokens: u64,  // Whole tokens, converted with the mint's decimals
+}
+
+// History of one distribution round - seeds [b"reward_round", pool, round]
+#[account]
+pub struct RewardRound {
+    pub round: u64,                     // 8 bytes
+    pub allocated_amount: u64,          // 8 bytes - Rewards allocated to stakers this round
+    pub total_weighted_staked: u64,     // 8 bytes - Weighted-stake denominator when the round was recorded
+    pub acc_reward_increment: u128,     // 16 bytes - Reward per weighted share added by this round (0 for Merkle rounds)
+    pub amount_assigned: u64,           // 8 bytes - This round's share of the stakes assigned or cranked while it was open (or claimed with proof)
+    pub stakes_assigned: u32,           // 4 bytes - Stakes assigned or cranked while the round was open (or claims made)
+    pub opened_at: i64,                 // 8 bytes
+    pub closed_at: i64,                 // 8 bytes - 0 until complete_monthly_distribution
+    pub undistributed_dust: u64,        // 8 bytes - Allocation lost to integer rounding, computed at close
+    pub merkle: bool,                   // 1 byte - Published with publish_reward_root
+}
+
+impl RewardRound {
+    pub fn open(
+        &mut self,
+        round: u64,
+        allocated_amount: u64,
+        total_weighted_staked: u64,
+        acc_reward_increment: u128,
+        merkle: bool,
+        opened_at: i64,
+    ) {
+        self.round = round;
+        self.allocated_amount = allocated_amount;
+        self.total_weighted_staked = total_weighted_staked;
+        self.acc_reward_increment = acc_reward_increment;
+        self.amount_assigned = 0;
+        self.stakes_assigned = 0;
+        self.opened_at = opened_at;
+        self.closed_at = 0;
+        self.undistributed_dust = 0;
+        self.merkle = merkle;
+    }
+
+    // Share of this round's rewards for a stake of `weighted_amount`, excluding anything from earlier rounds
+    pub fn round_share(&self, weighted_amount: u64) -> u64 {
+        self.acc_reward_increment
+            .checked_mul(weighted_amount as u128).unwrap()
+            .checked_div(ACC_REWARD_PRECISION).unwrap() as u64
+    }
+
+    pub fn record_assignment(&mut self, amount: u64) {
+        self.amount_assigned = self.amount_assigned.checked_add(amount).unwrap();
+        self.stakes_assigned = self.stakes_assigned.checked_add(1).unwrap();
+    }
+}
+
+// Merkle reward round published by publish_reward_root - seeds [b"reward_root", pool, round]
+#[account]
+pub struct RewardRoot {
+    pub round: u64,             // 8 bytes
+    pub root: [u8; 32],         // 32 bytes - Merkle root of (user, stake_index, amount) leaves
+    pub total_amount: u64,      // 8 bytes - Total rewards in the tree
+    pub claimed_amount: u64,    // 8 bytes - Rewards claimed so far
+    pub leaf_count: u32,        // 4 bytes
+    pub published_at: i64,      // 8 bytes
+    pub claimed_bitmap: Vec<u8>, // 4 + ceil(leaf_count / 8) bytes - One bit per leaf index
+}
+
+impl RewardRoot {
+    pub fn is_claimed(&self, leaf_index: u32) -> bool {
+        self.claimed_bitmap[(leaf_index / 8) as usize] & (1 << (leaf_index % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, leaf_index: u32) {
+        self.claimed_bitmap[(leaf_index / 8) as usize] |= 1 << (leaf_index % 8);
+    }
+}
 
-// Global distribution state account
+// Per-user record of RR3 burned through user_burn_tokens
+#[account]
+pub struct UserBurnRecord {
+    pub user: Pubkey,           // 32 bytes
+    pub total_burned: u64,      // 8 bytes - Cumulative RR3 burned by this user
+    pub burn_count: u32,        // 4 bytes - Number of burns
+    pub last_burn_time: i64,    // 8 bytes
+}
+
+// Per-pool distribution state account
 #[account]
 pub struct DistributionState {
     pub admin: Pubkey,                    // 32 bytes
     pub last_distribution_time: i64,      // 8 bytes - Unix timestamp of last distribution
-    pub total_rr3_staked: u64,           // 8 bytes - Total RR3 staked across all users
-    pub monthly_rr3_for_rewards: u64,     // 8 bytes - RR3 tokens allocated for monthly reward distribution
-    pub monthly_expense_fees: u64,        // 8 bytes - Expense wallet amount (0.33%)
-    pub monthly_marketing_fees: u64,      // 8 bytes - Marketing wallet amount (0.03%)
-    pub monthly_burn_fees: u64,           // 8 bytes - Burn wallet amount (0.003%)
+    pub total_rr3_staked: u64,           // 8 bytes - Total weighted RR3 staked across all users (maintained on-chain)
+    pub acc_reward_per_weighted_share: u128, // 16 bytes - Cumulative rewards per weighted unit, scaled by ACC_REWARD_PRECISION
+    pub total_bonus_liability: u64,       // 8 bytes - Worst-case completion bonuses owed to active stakes
+    pub monthly_rr3_for_rewards: u64,     // 8 bytes - RR3 tokens allocated to stakers by the current round
+    pub total_fee_commission: u64,        // 8 bytes - Entry commission routed to the fee wallet (3.00%)
+    pub total_expense_commission: u64,    // 8 bytes - Entry commission routed to the expense wallet (0.33%)
+    pub total_marketing_commission: u64,  // 8 bytes - Entry commission routed to the marketing wallet (0.03%)
     pub distribution_round: u64,          // 8 bytes - Current distribution round number
+    pub fee_wallet: Pubkey,               // 32 bytes - RR3 token account receiving the fee wallet commission
+    pub expense_wallet: Pubkey,           // 32 bytes - RR3 token account receiving the expense commission
+    pub marketing_wallet: Pubkey,         // 32 bytes - RR3 token account receiving the marketing commission
+    pub pending_admin: Pubkey,            // 32 bytes - Proposed admin, must call accept_admin (default = none)
+    pub distributor: Pubkey,              // 32 bytes - Records rounds and settles rewards
+    pub treasurer: Pubkey,                // 32 bytes - Manages the commission wallets and burns collected fees
+    pub pauser: Pubkey,                   // 32 bytes - Pauses the program in an emergency
+    pub paused: u8,                       // 1 byte - Bitflags of paused operations (PAUSE_*)
+    pub total_buyback_principal: u64,     // 8 bytes - Principal moved into the buyback treasury by emergency buybacks
+    pub total_buyback_paid: u64,          // 8 bytes - RR3 paid to users by emergency buybacks
+    pub total_user_burned: u64,           // 8 bytes - RR3 burned by users through user_burn_tokens
+    pub reward_carryover: u64,            // 8 bytes - Leftover of closed rounds added to the next recorded round
+    pub carryover_sink: Pubkey,           // 32 bytes - Token account receiving round leftovers instead (default = carry over)
+    pub total_principal: u64,             // 8 bytes - Principal of all active stakes, always backed by the principal vault
+    pub reward_liability: u64,            // 8 bytes - Rewards owed from the rewards vault by open rounds and unclaimed settlements
+    pub total_commission_burned: u64,     // 8 bytes - Fee wallet commission burned by the treasurer through burn_fees
+}
+
+impl DistributionState {
+    // Add a routed entry commission to the per-wallet commission totals
+    pub fn record_commission(&mut self, fee_amount: u64, expense_amount: u64, marketing_amount: u64) {
+        self.total_fee_commission = self.total_fee_commission.checked_add(fee_amount).unwrap();
+        self.total_expense_commission = self.total_expense_commission.checked_add(expense_amount).unwrap();
+        self.total_marketing_commission = self.total_marketing_commission.checked_add(marketing_amount).unwrap();
+    }
+
+    pub fn require_not_paused(&self, flag: u8) -> Result<()> {
+        require!(self.paused & flag == 0, StakeError::Paused);
+        Ok(())
+    }
+
+    // Owe newly allocated rewards from the rewards vault, which must hold them on top of everything already owed
+    pub fn commit_rewards(&mut self, amount: u64, rewards_vault_balance: u64) -> Result<()> {
+        let reward_liability = self.reward_liability.checked_add(amount).unwrap();
+        require!(rewards_vault_balance >= reward_liability, StakeError::InsufficientTreasuryFunds);
+        self.reward_liability = reward_liability;
+        Ok(())
+    }
+
+    // Release owed rewards that were paid out, forfeited or left over at round close
+    pub fn release_rewards(&mut self, amount: u64) {
+        self.reward_liability = self.reward_liability.saturating_sub(amount);
+    }
+
+    // Reject a new round before last_distribution_time + round_interval, logging when it becomes allowed
+    pub fn require_round_interval_elapsed(&self, round_interval: i64, now: i64) -> Result<()> {
+        let next_allowed_time = self.last_distribution_time.checked_add(round_interval).unwrap();
+        if now < next_allowed_time {
+            msg!("Next distribution round allowed at {} ({} seconds from now)", next_allowed_time, next_allowed_time - now);
+            return Err(error!(StakeError::TooEarlyForDistribution).with_values((now, next_allowed_time)));
+        }
+        Ok(())
+    }
+
+    // Reject a new round while the current one (if any) has not been closed by complete_monthly_distribution
+    pub fn require_previous_round_closed(&self, previous_round: Option<&RewardRound>) -> Result<()> {
+        if self.distribution_round == 0 {
+            return Ok(());
+        }
+        let previous_round = previous_round.ok_or(StakeError::PreviousRoundOpen)?;
+        require!(previous_round.closed_at != 0, StakeError::PreviousRoundOpen);
+        Ok(())
+    }
 }
 
 
 
-// Initialize distribution state context
+// Initialize pool context
 #[derive(Accounts)]
-pub struct InitializeDistributionState<'info> {
+#[instruction(mint: Pubkey)]
+pub struct InitializePool<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8, // discriminator + pubkey + 7 u64s/i64s (added marketing and burn fees)
-        seeds = [b"distribution_state"],
+        space = 8 + 32 + 1 + 8 + 8 + 1 + 3, // discriminator + mint + decimals + min stake + created_at + bump + vault bumps
+        seeds = [b"pool", mint.as_ref()],
         bump,
     )]
-    pub distribution_state: Account<'info, DistributionState>,
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 32 + 8 + 8 + 16 + 8 + 8 + 8 + 8 + 8 + 8 + 32 * 3 + 32 * 4 + 1 + 8 + 8 + 8 + 8 + 32 + 8 + 8 + 8, // discriminator + pubkey + 8 u64s/i64s + u128 accumulator + 3 commission wallets + pending admin and 3 roles + pause flags + buyback totals + user burn total + carryover + carryover sink + total principal + reward liability + commission burn total
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 8 + 8 + 8 + 4 + MAX_LOCK_TIERS * (8 + 8 + 8 + 1), // discriminator + buyback discount + keeper tip + round interval + vec length + tiers
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Box<Account<'info, StakingConfig>>,
+
+    #[account(address = mint)]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
     pub admin: Signer<'info>,
 
+    // Only the program's upgrade authority can create pools, so nobody can squat a mint's pool
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Staker>,
+
+    /// CHECK: Bound to the program by the constraint above, deserialized in the handler to read the upgrade authority
+    pub program_data: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
-// Record monthly collection context
+// Propose admin context
 #[derive(Accounts)]
-pub struct RecordMonthlyCollection<'info> {
+pub struct ProposeAdmin<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = admin,
+    )]
+    pub distribution_state: Account<'info, DistributionState>,
+
+    pub admin: Signer<'info>,
+}
+
+// Accept admin context
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = pending_admin @ StakeError::NotPendingAdmin,
+    )]
+    pub distribution_state: Account<'info, DistributionState>,
+
+    pub pending_admin: Signer<'info>,
+}
+
+// Set roles context
+#[derive(Accounts)]
+pub struct SetRoles<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = admin,
+    )]
+    pub distribution_state: Account<'info, DistributionState>,
+
+    pub admin: Signer<'info>,
+}
+
+// Set pause flags context
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = pauser,
+    )]
+    pub distribution_state: Account<'info, DistributionState>,
+
+    pub pauser: Signer<'info>,
+}
+
+// Add / retire / update lock tier context
+#[derive(Accounts)]
+pub struct UpdateStakingConfig<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Account<'info, StakingConfig>,
+
+    #[account(
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = admin,
+    )]
+    pub distribution_state: Account<'info, DistributionState>,
+
+    pub admin: Signer<'info>,
+}
+
+// Set commission wallets context
+#[derive(Accounts)]
+pub struct SetCommissionWallets<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = treasurer,
+    )]
+    pub distribution_state: Account<'info, DistributionState>,
+
+    #[account(token::mint = rr3_mint)]
+    pub fee_wallet_token_bag: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(token::mint = rr3_mint)]
+    pub expense_wallet_token_bag: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(token::mint = rr3_mint)]
+    pub marketing_wallet_token_bag: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    pub treasurer: Signer<'info>,
+}
+
+// Set carryover sink context
+#[derive(Accounts)]
+pub struct SetCarryoverSink<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
     #[account(
         mut,
-        seeds = [b"distribution_state"],
+        seeds = [b"distribution_state", pool.key().as_ref()],
         bump,
         has_one = admin,
     )]
     pub distribution_state: Account<'info, DistributionState>,
 
+    #[account(token::mint = rr3_mint)]
+    pub carryover_sink_token_bag: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
     pub admin: Signer<'info>,
 }
 
-// Update total staked context
+// Record monthly collection context
+#[derive(Accounts)]
+pub struct RecordMonthlyCollection<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = distributor,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    // Staking config holding the round interval
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Box<Account<'info, StakingConfig>>,
+
+    // History record of the current round, which must be closed; omitted before the first round
+    #[account(
+        seeds = [b"reward_round", pool.key().as_ref(), distribution_state.distribution_round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub previous_reward_round: Option<Box<Account<'info, RewardRound>>>,
+
+    // History record of the round being opened
+    #[account(
+        init,
+        payer = distributor,
+        space = 8 + 8 + 8 + 8 + 16 + 8 + 4 + 8 + 8 + 8 + 1, // discriminator + RewardRound fields
+        seeds = [b"reward_round", pool.key().as_ref(), (distribution_state.distribution_round + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_round: Box<Account<'info, RewardRound>>,
+
+    // Rewards vault funding the round
+    #[account(
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub distributor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Fund and record round context
+#[derive(Accounts)]
+pub struct FundAndRecordRound<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = distributor,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    // Staking config holding the round interval
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Box<Account<'info, StakingConfig>>,
+
+    // History record of the current round, which must be closed; omitted before the first round
+    #[account(
+        seeds = [b"reward_round", pool.key().as_ref(), distribution_state.distribution_round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub previous_reward_round: Option<Box<Account<'info, RewardRound>>>,
+
+    // History record of the round being opened
+    #[account(
+        init,
+        payer = distributor,
+        space = 8 + 8 + 8 + 8 + 16 + 8 + 4 + 8 + 8 + 8 + 1, // discriminator + RewardRound fields
+        seeds = [b"reward_round", pool.key().as_ref(), (distribution_state.distribution_round + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_round: Box<Account<'info, RewardRound>>,
+
+    // Rewards vault receiving the deposit
+    #[account(
+        mut,
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Distributor's RR3 token account funding the round
+    #[account(
+        mut,
+        token::mint = rr3_mint,
+        token::authority = distributor,
+    )]
+    pub distributor_rr3_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub distributor: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Publish reward root context
+#[derive(Accounts)]
+#[instruction(round: u64, root: [u8; 32], total: u64, leaf_count: u32)]
+pub struct PublishRewardRoot<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = distributor,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    // Staking config holding the round interval
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Box<Account<'info, StakingConfig>>,
+
+    #[account(
+        init,
+        payer = distributor,
+        space = 8 + 8 + 32 + 8 + 8 + 4 + 8 + 4 + (leaf_count as usize).div_ceil(8), // discriminator + fields + bitmap
+        seeds = [b"reward_root", pool.key().as_ref(), round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_root: Box<Account<'info, RewardRoot>>,
+
+    // History record of the current round, which must be closed; omitted before the first round
+    #[account(
+        seeds = [b"reward_round", pool.key().as_ref(), distribution_state.distribution_round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub previous_reward_round: Option<Box<Account<'info, RewardRound>>>,
+
+    // History record of the round being opened
+    #[account(
+        init,
+        payer = distributor,
+        space = 8 + 8 + 8 + 8 + 16 + 8 + 4 + 8 + 8 + 8 + 1, // discriminator + RewardRound fields
+        seeds = [b"reward_round", pool.key().as_ref(), (distribution_state.distribution_round + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_round: Box<Account<'info, RewardRound>>,
+
+    // Treasury that pays the claims
+    #[account(
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub distributor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Claim with proof context
+#[derive(Accounts)]
+#[instruction(round: u64)]
+pub struct ClaimWithProof<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_root", pool.key().as_ref(), round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_root: Box<Account<'info, RewardRoot>>,
+
+    // History record of the claimed round
+    #[account(
+        mut,
+        seeds = [b"reward_round", pool.key().as_ref(), round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_round: Box<Account<'info, RewardRound>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = rr3_mint,
+    )]
+    pub user_rr3_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    pub user_authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Reconcile total staked context
 #[derive(Accounts)]
-pub struct UpdateTotalStaked<'info> {
+pub struct ReconcileTotalStaked<'info> {
+    // Pool this instruction operates on
     #[account(
-        mut,
-        seeds = [b"distribution_state"],
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [b"distribution_state", pool.key().as_ref()],
         bump,
         has_one = admin,
     )]
@@ -740,16 +3065,31 @@ pub struct UpdateTotalStaked<'info> {
 #[derive(Accounts)]
 #[instruction(stake_index: u32)]
 pub struct AssignMonthlyRewards<'info> {
+    // Pool this instruction operates on
     #[account(
-        seeds = [b"distribution_state"],
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [b"distribution_state", pool.key().as_ref()],
         bump,
-        has_one = admin,
+        has_one = distributor,
     )]
     pub distribution_state: Account<'info, DistributionState>,
 
+    // History record of the current round
     #[account(
         mut,
-        seeds = [b"stake", staker.key().as_ref(), stake_index.to_le_bytes().as_ref()],
+        seeds = [b"reward_round", pool.key().as_ref(), distribution_state.distribution_round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_round: Box<Account<'info, RewardRound>>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", pool.key().as_ref(), staker.key().as_ref(), stake_index.to_le_bytes().as_ref()],
         bump,
     )]
     pub stake_record: Account<'info, StakeRecord>,
@@ -757,192 +3097,288 @@ pub struct AssignMonthlyRewards<'info> {
     /// CHECK: The staker's public key for PDA derivation
     pub staker: AccountInfo<'info>,
 
-    pub admin: Signer<'info>,
+    pub distributor: Signer<'info>,
 }
 
-// Transfer expense fees context
+// Crank rewards context - stake records are passed as writable remaining_accounts
 #[derive(Accounts)]
-pub struct TransferExpenseFees<'info> {
+pub struct CrankRewards<'info> {
+    // Pool this instruction operates on
     #[account(
-        mut,
-        seeds = [b"distribution_state"],
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [b"distribution_state", pool.key().as_ref()],
         bump,
-        has_one = admin,
     )]
-    pub distribution_state: Account<'info, DistributionState>,
+    pub distribution_state: Box<Account<'info, DistributionState>>,
 
+    // History record of the current round
     #[account(
         mut,
-        seeds = [b"treasury_sol"],
+        seeds = [b"reward_round", pool.key().as_ref(), distribution_state.distribution_round.to_le_bytes().as_ref()],
         bump,
     )]
-    /// CHECK: This is the treasury SOL account PDA
-    pub treasury_sol_account: AccountInfo<'info>,
-
-    #[account(mut)]
-    /// CHECK: This is the expense wallet SOL account
-    pub expense_wallet_sol_account: AccountInfo<'info>,
+    pub reward_round: Box<Account<'info, RewardRound>>,
 
-    pub admin: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
+    // Keeper tip per stake
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Box<Account<'info, StakingConfig>>,
 
-// Transfer marketing fees context
-#[derive(Accounts)]
-pub struct TransferMarketingFees<'info> {
     #[account(
         mut,
-        seeds = [b"distribution_state"],
+        seeds = [b"keeper_incentive", pool.key().as_ref()],
         bump,
-        has_one = admin,
     )]
-    pub distribution_state: Account<'info, DistributionState>,
+    pub keeper_incentive_bag: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    // Keeper's token account receiving the tip
     #[account(
         mut,
-        seeds = [b"treasury_sol"],
-        bump,
+        token::mint = rr3_mint,
     )]
-    /// CHECK: This is the treasury SOL account PDA
-    pub treasury_sol_account: AccountInfo<'info>,
+    pub keeper_rr3_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(mut)]
-    /// CHECK: This is the marketing wallet SOL account
-    pub marketing_wallet_sol_account: AccountInfo<'info>,
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
-    pub admin: Signer<'info>,
+    pub keeper: Signer<'info>,
 
-    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-// Transfer fee wallet context
+// Emergency buyback context
 #[derive(Accounts)]
-pub struct TransferFeeWallet<'info> {
+#[instruction(stake_index: u32)]
+pub struct EmergencyBuyback<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
     #[account(
         mut,
-        seeds = [b"distribution_state"],
+        seeds = [b"stake", pool.key().as_ref(), user_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
         bump,
-        has_one = admin,
+        close = user_authority, // Close the account and refund rent
     )]
-    pub distribution_state: Account<'info, DistributionState>,
+    pub stake_record: Account<'info, StakeRecord>,
 
     #[account(
         mut,
-        seeds = [b"treasury_sol"],
+        seeds = [b"user_counter", pool.key().as_ref(), user_authority.key().as_ref()],
         bump,
     )]
-    /// CHECK: This is the treasury SOL account PDA
-    pub treasury_sol_account: AccountInfo<'info>,
+    pub user_stake_counter: Account<'info, UserStakeCounter>,
 
     #[account(mut)]
-    /// CHECK: This is the fee wallet SOL account
-    pub fee_wallet_sol_account: AccountInfo<'info>,
+    pub user_rr3_token_bag: InterfaceAccount<'info, TokenAccount>,
 
-    pub admin: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
+    pub user_authority: Signer<'info>,
 
-// Burn fees context
-#[derive(Accounts)]
-pub struct BurnFees<'info> {
     #[account(
         mut,
-        seeds = [b"distribution_state"],
+        seeds = [b"buyback_treasury", pool.key().as_ref()],
         bump,
-        has_one = admin,
     )]
-    pub distribution_state: Account<'info, DistributionState>,
+    pub buyback_treasury_bag: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    // Staking bag holding the principal being bought back
     #[account(
         mut,
-        seeds = [b"treasury_sol"],
+        seeds = [b"staking_bag", pool.key().as_ref()],
+        bump = pool.principal_vault_bump,
+    )]
+    pub principal_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
         bump,
     )]
-    /// CHECK: This is the treasury SOL account PDA
-    pub treasury_sol_account: AccountInfo<'info>,
+    pub distribution_state: Box<Account<'info, DistributionState>>,
 
-    #[account(mut)]
-    /// CHECK: This is the burn wallet SOL account
-    pub burn_wallet_sol_account: AccountInfo<'info>,
+    // Buyback discount
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Box<Account<'info, StakingConfig>>,
 
-    pub admin: Signer<'info>,
+    // Treasury holding the forfeited rewards, paid to the carryover sink when one is configured
+    #[account(
+        mut,
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Required only when distribution_state.carryover_sink is set and rewards are forfeited
+    #[account(
+        mut,
+        address = distribution_state.carryover_sink,
+    )]
+    pub carryover_sink_token_bag: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-// Emergency buyback context
+// User burn tokens context
 #[derive(Accounts)]
-#[instruction(stake_index: u32)]
-pub struct EmergencyBuyback<'info> {
+pub struct UserBurnTokens<'info> {
+    // Pool this instruction operates on
     #[account(
-        mut,
-        seeds = [b"stake", user_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
-        bump,
-        close = user_authority, // Close the account and refund rent
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
     )]
-    pub stake_record: Account<'info, StakeRecord>,
+    pub pool: Box<Account<'info, Pool>>,
 
     #[account(
         mut,
-        seeds = [b"user_counter", user_authority.key().as_ref()],
-        bump,
+        token::mint = rr3_mint,
+        token::authority = user_authority,
     )]
-    pub user_stake_counter: Account<'info, UserStakeCounter>,
+    pub user_rr3_token_bag: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
-    pub user_rr3_token_bag: Account<'info, TokenAccount>,
-
     pub user_authority: Signer<'info>,
 
+    #[account(
+        init_if_needed,
+        payer = user_authority,
+        space = 60, // 8 + 32 + 8 + 4 + 8 = 60 bytes
+        seeds = [b"user_burn", pool.key().as_ref(), user_authority.key().as_ref()],
+        bump,
+    )]
+    pub user_burn_record: Account<'info, UserBurnRecord>,
+
+    // Protocol-wide burn total
     #[account(
         mut,
-        seeds = [b"buyback_treasury"],
+        seeds = [b"distribution_state", pool.key().as_ref()],
         bump,
     )]
-    pub buyback_treasury_bag: Account<'info, TokenAccount>,
+    pub distribution_state: Account<'info, DistributionState>,
 
     #[account(
-        address = RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap(),
+        mut,
+        address = pool.mint,
     )]
-    pub rr3_mint: Account<'info, Mint>,
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-// User burn tokens context
+// Burn fees context
 #[derive(Accounts)]
-pub struct UserBurnTokens<'info> {
-    #[account(mut)]
-    pub user_rr3_token_bag: Account<'info, TokenAccount>,
+pub struct BurnFees<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
 
-    pub user_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+        has_one = treasurer,
+    )]
+    pub distribution_state: Account<'info, DistributionState>,
 
-    #[account(mut)]
-    pub burn_wallet_token_bag: Account<'info, TokenAccount>,
+    // Fee wallet RR3 token account configured via set_commission_wallets, owned by the treasurer
+    #[account(
+        mut,
+        address = distribution_state.fee_wallet,
+        token::authority = treasurer,
+    )]
+    pub fee_wallet_token_bag: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        address = RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap(),
+        mut,
+        address = pool.mint,
     )]
-    pub rr3_mint: Account<'info, Mint>,
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    pub treasurer: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 // Complete monthly distribution context
 #[derive(Accounts)]
 pub struct CompleteMonthlyDistribution<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
     #[account(
         mut,
-        seeds = [b"distribution_state"],
+        seeds = [b"distribution_state", pool.key().as_ref()],
         bump,
-        has_one = admin,
+        has_one = distributor,
     )]
-    pub distribution_state: Account<'info, DistributionState>,
+    pub distribution_state: Box<Account<'info, DistributionState>>,
 
-    pub admin: Signer<'info>,
+    // History record of the current round
+    #[account(
+        mut,
+        seeds = [b"reward_round", pool.key().as_ref(), distribution_state.distribution_round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_round: Box<Account<'info, RewardRound>>,
+
+    // Merkle root of the current round, required only when the round is a Merkle round
+    #[account(
+        seeds = [b"reward_root", pool.key().as_ref(), distribution_state.distribution_round.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub reward_root: Option<Box<Account<'info, RewardRoot>>>,
+
+    // Treasury holding the leftover, paid to the carryover sink when one is configured
+    #[account(
+        mut,
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Required only when distribution_state.carryover_sink is set
+    #[account(
+        mut,
+        address = distribution_state.carryover_sink,
+    )]
+    pub carryover_sink_token_bag: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    pub distributor: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 // SOL Treasury account context
@@ -965,186 +3401,390 @@ pub struct CreateSOLTreasury<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(program_rr3_bag_bump: u8, stake_index: u32)]
+#[instruction(stake_index: u32)]
 pub struct Stake<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
     // Minimized for stack space - core accounts only
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 
     #[account(mut)]
-    pub user_rr3_token_bag: Account<'info, TokenAccount>,
+    pub user_rr3_token_bag: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub user_rr3_token_bag_authority: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [ rr3_mint.key().as_ref() ],
-        bump = program_rr3_bag_bump,
+        seeds = [b"staking_bag", pool.key().as_ref()],
+        bump = pool.principal_vault_bump,
     )]
-    pub program_rr3_token_bag: Account<'info, TokenAccount>,
+    pub principal_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        address = RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap(),
+        address = pool.mint,
     )]
-    pub rr3_mint: Account<'info, Mint>,
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         init,
         payer = user_rr3_token_bag_authority,
-        space = 108, // 8 + 32 + 4 + 8*8 = 108 bytes
-        seeds = [b"stake", user_rr3_token_bag_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
+        space = 140, // 8 + 32 + 4 + 8*8 + 16 + 8*2 = 140 bytes
+        seeds = [b"stake", pool.key().as_ref(), user_rr3_token_bag_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub stake_record: Account<'info, StakeRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = user_rr3_token_bag_authority,
+        space = 48, // 8 + 32 + 4 + 4 = 48 bytes
+        seeds = [b"user_counter", pool.key().as_ref(), user_rr3_token_bag_authority.key().as_ref()],
+        bump,
+    )]
+    pub user_stake_counter: Account<'info, UserStakeCounter>,
+
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Box<Account<'info, StakingConfig>>,
+
+    // Bonus reserve backing the completion bonus of this stake
+    #[account(
+        seeds = [b"bonus_reserve", pool.key().as_ref()],
+        bump = pool.bonus_reserve_bump,
+    )]
+    pub bonus_reserve_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Commission destinations configured via set_commission_wallets
+    #[account(mut, address = distribution_state.fee_wallet)]
+    pub fee_wallet_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = distribution_state.expense_wallet)]
+    pub expense_wallet_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = distribution_state.marketing_wallet)]
+    pub marketing_wallet_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(stake_index: u32)]
+pub struct AddStake<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    // SPL Token Program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // Associated Token Account for User which holds RR3.
+    #[account(mut)]
+    pub user_rr3_token_bag: InterfaceAccount<'info, TokenAccount>,
+
+    // The authority allowed to mutate the above ⬆️
+    #[account(mut)]
+    pub user_rr3_token_bag_authority: Signer<'info>,
+
+    // Used to receive RR3 from users (staking bag)
+    #[account(
+        mut,
+        seeds = [b"staking_bag", pool.key().as_ref()],
+        bump = pool.principal_vault_bump,
+    )]
+    pub principal_vault: InterfaceAccount<'info, TokenAccount>,
+
+    // Required for the PDA above ⬆️
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    // Existing stake record account (must exist)
+    #[account(
+        mut,
+        seeds = [b"stake", pool.key().as_ref(), user_rr3_token_bag_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub stake_record: Account<'info, StakeRecord>,
+
+    // Global distribution state (weighted total staked)
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    // Lock tier table
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Box<Account<'info, StakingConfig>>,
+
+    // Bonus reserve backing the completion bonus of this stake
+    #[account(
+        seeds = [b"bonus_reserve", pool.key().as_ref()],
+        bump = pool.bonus_reserve_bump,
+    )]
+    pub bonus_reserve_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Commission destinations configured via set_commission_wallets
+    #[account(mut, address = distribution_state.fee_wallet)]
+    pub fee_wallet_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = distribution_state.expense_wallet)]
+    pub expense_wallet_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = distribution_state.marketing_wallet)]
+    pub marketing_wallet_token_bag: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// New context for claiming RR3 rewards
+#[derive(Accounts)]
+#[instruction(stake_index: u32)]
+pub struct ClaimRR3Rewards<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(mut)]
+    pub user_rr3_token_bag: InterfaceAccount<'info, TokenAccount>,
+
+    pub user_authority: Signer<'info>,
+
+    // Rewards vault paying monthly rewards
+    #[account(
+        mut,
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    // User's stake record with index
+    #[account(
+        mut,
+        seeds = [b"stake", pool.key().as_ref(), user_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
         bump,
     )]
     pub stake_record: Account<'info, StakeRecord>,
 
+    // Global distribution state (reward accumulator and liability)
     #[account(
-        init_if_needed,
-        payer = user_rr3_token_bag_authority,
-        space = 48, // 8 + 32 + 4 + 4 = 48 bytes
-        seeds = [b"user_counter", user_rr3_token_bag_authority.key().as_ref()],
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
         bump,
     )]
-    pub user_stake_counter: Account<'info, UserStakeCounter>,
+    pub distribution_state: Account<'info, DistributionState>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(program_rr3_bag_bump: u8)]
-pub struct AddStake<'info> {
-    // SPL Token Program
-    pub token_program: Program<'info, Token>,
+#[instruction(stake_index: u32)]
+pub struct UnStake<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
 
-    // Associated Token Account for User which holds RR3.
-    #[account(mut)]
-    pub user_rr3_token_bag: Account<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
 
-    // The authority allowed to mutate the above ⬆️
     #[account(mut)]
+    pub user_rr3_token_bag: InterfaceAccount<'info, TokenAccount>,
+
     pub user_rr3_token_bag_authority: Signer<'info>,
 
-    // Used to receive RR3 from users (staking bag)
+    // Staking bag to return principal + rewards from
     #[account(
         mut,
-        seeds = [ rr3_mint.key().as_ref() ],
-        bump = program_rr3_bag_bump,
+        seeds = [b"staking_bag", pool.key().as_ref()],
+        bump = pool.principal_vault_bump,
     )]
-    pub program_rr3_token_bag: Account<'info, TokenAccount>,
+    pub principal_vault: InterfaceAccount<'info, TokenAccount>,
 
-    // Required for the PDA above ⬆️
     #[account(
-        address = RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap(),
+        address = pool.mint,
     )]
-    pub rr3_mint: Account<'info, Mint>,
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
-    // Existing stake record account (must exist)
+    // User's stake record with index
     #[account(
         mut,
-        seeds = [b"stake", user_rr3_token_bag_authority.key().as_ref()],
+        seeds = [b"stake", pool.key().as_ref(), user_rr3_token_bag_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
         bump,
+        close = user_rr3_token_bag_authority, // Close the account and refund rent
     )]
     pub stake_record: Account<'info, StakeRecord>,
 
-    pub system_program: Program<'info, System>,
-}
-
-// New context for claiming RR3 rewards
-#[derive(Accounts)]
-#[instruction(stake_index: u32, _program_rr3_bag_bump: u8)]
-pub struct ClaimRR3Rewards<'info> {
-    pub token_program: Program<'info, Token>,
-
-    #[account(mut)]
-    pub user_rr3_token_bag: Account<'info, TokenAccount>,
+    // User stake counter to update when unstaking
+    #[account(
+        mut,
+        seeds = [b"user_counter", pool.key().as_ref(), user_rr3_token_bag_authority.key().as_ref()],
+        bump,
+    )]
+    pub user_stake_counter: Account<'info, UserStakeCounter>,
 
-    pub user_authority: Signer<'info>,
+    // Global distribution state (weighted total staked)
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
 
-    // Program's RR3 token bag (treasury for rewards)
+    // Treasury paying monthly rewards
     #[account(
         mut,
-        seeds = [b"token_bag"],
-        bump = _program_rr3_bag_bump,
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
     )]
-    pub program_rr3_token_bag: Account<'info, TokenAccount>,
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    // User's stake record with index
+    // Bonus reserve paying the completion bonus
     #[account(
         mut,
-        seeds = [b"stake", user_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
-        bump,
+        seeds = [b"bonus_reserve", pool.key().as_ref()],
+        bump = pool.bonus_reserve_bump,
     )]
-    pub stake_record: Account<'info, StakeRecord>,
+    pub bonus_reserve_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(program_rr3_bag_bump: u8, stake_index: u32)]
-pub struct UnStake<'info> {
-    pub token_program: Program<'info, Token>,
+#[instruction(stake_index: u32)]
+pub struct EmergencyWithdraw<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 
     #[account(mut)]
-    pub user_rr3_token_bag: Account<'info, TokenAccount>,
+    pub user_rr3_token_bag: InterfaceAccount<'info, TokenAccount>,
 
     pub user_rr3_token_bag_authority: Signer<'info>,
 
-    // Staking bag to return principal + rewards from
+    // Staking bag to return principal from
     #[account(
         mut,
-        seeds = [ rr3_mint.key().as_ref() ],
-        bump = program_rr3_bag_bump,
+        seeds = [b"staking_bag", pool.key().as_ref()],
+        bump = pool.principal_vault_bump,
     )]
-    pub program_rr3_token_bag: Account<'info, TokenAccount>,
+    pub principal_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        address = RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap(),
+        address = pool.mint,
     )]
-    pub rr3_mint: Account<'info, Mint>,
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
     // User's stake record with index
     #[account(
         mut,
-        seeds = [b"stake", user_rr3_token_bag_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
+        seeds = [b"stake", pool.key().as_ref(), user_rr3_token_bag_authority.key().as_ref(), stake_index.to_le_bytes().as_ref()],
         bump,
         close = user_rr3_token_bag_authority, // Close the account and refund rent
     )]
     pub stake_record: Account<'info, StakeRecord>,
 
-    // User stake counter to update when unstaking
     #[account(
         mut,
-        seeds = [b"user_counter", user_rr3_token_bag_authority.key().as_ref()],
+        seeds = [b"user_counter", pool.key().as_ref(), user_rr3_token_bag_authority.key().as_ref()],
         bump,
     )]
     pub user_stake_counter: Account<'info, UserStakeCounter>,
 
-    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    // Treasury holding the forfeited rewards, paid to the carryover sink when one is configured
+    #[account(
+        mut,
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Required only when distribution_state.carryover_sink is set and rewards are forfeited
+    #[account(
+        mut,
+        address = distribution_state.carryover_sink,
+    )]
+    pub carryover_sink_token_bag: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 }
 
 #[derive(Accounts)]
 pub struct CreateRR3TokenBag<'info> {
+    // Pool this instruction operates on
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
     // 1. PDA (so pubkey) for the soon-to-be created RR3 token bag for our program.
     #[account(
         init,
         payer = payer,
 
-        // We use the token mint as a seed for the mapping -> think "HashMap[seeds+bump] = pda"
-        seeds = [ RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap().as_ref() ],
+        // We use the pool as a seed for the mapping -> think "HashMap[seeds+bump] = pda"
+        seeds = [b"staking_bag", pool.key().as_ref()],
         bump,
 
         // Token Program wants to know what kind of token this token bag is for
         token::mint = rr3_mint,
 
         // It's a PDA so the authority is itself!
-        token::authority = program_rr3_token_bag,
+        token::authority = principal_vault,
     )]
-    pub program_rr3_token_bag: Account<'info, TokenAccount>,
+    pub principal_vault: InterfaceAccount<'info, TokenAccount>,
 
     // 2. The mint 🌈🛤️ because it's needed from above ⬆️ token::mint = ...
     #[account(
-        address = RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap(),
+        address = pool.mint,
     )]
-    pub rr3_mint: Account<'info, Mint>,
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
     // 3. The rent payer
     #[account(mut)]
@@ -1152,88 +3792,537 @@ pub struct CreateRR3TokenBag<'info> {
 
     // 4. Needed from Anchor for the creation of an Associated Token Account
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
 pub struct CreateBuybackTreasury<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
     #[account(
         init,
         payer = payer,
-        seeds = [b"buyback_treasury"],
+        seeds = [b"buyback_treasury", pool.key().as_ref()],
         bump,
         token::mint = rr3_mint,
         token::authority = buyback_treasury_bag,
     )]
-    pub buyback_treasury_bag: Account<'info, TokenAccount>,
+    pub buyback_treasury_bag: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBonusReserve<'info> {
+    // Pool this instruction operates on
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"bonus_reserve", pool.key().as_ref()],
+        bump,
+        token::mint = rr3_mint,
+        token::authority = bonus_reserve_vault,
+    )]
+    pub bonus_reserve_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = pool.mint,
+    )]
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CreateKeeperIncentivePool<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"keeper_incentive", pool.key().as_ref()],
+        bump,
+        token::mint = rr3_mint,
+        token::authority = keeper_incentive_bag,
+    )]
+    pub keeper_incentive_bag: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        address = RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap(),
+        address = pool.mint,
     )]
-    pub rr3_mint: Account<'info, Mint>,
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
 pub struct CreateTreasuryBag<'info> {
+    // Pool this instruction operates on
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
     #[account(
         init,
         payer = payer,
-        seeds = [b"token_bag"],
+        seeds = [b"token_bag", pool.key().as_ref()],
         bump,
         token::mint = rr3_mint,
-        token::authority = treasury_bag,
+        token::authority = rewards_vault,
     )]
-    pub treasury_bag: Account<'info, TokenAccount>,
+    pub rewards_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        address = RR3_MINT_ADDRESS.parse::<Pubkey>().unwrap(),
+        address = pool.mint,
     )]
-    pub rr3_mint: Account<'info, Mint>,
+    pub rr3_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 // Get lock period info context
 #[derive(Accounts)]
 pub struct GetLockPeriodInfo<'info> {
-    /// CHECK: This function is read-only and doesn't require any accounts
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Account<'info, StakingConfig>,
+
     pub signer: Signer<'info>,
 }
 
+// Get pool stats context
+#[derive(Accounts)]
+pub struct GetPoolStats<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+    )]
+    pub distribution_state: Box<Account<'info, DistributionState>>,
+
+    #[account(
+        seeds = [b"staking_bag", pool.key().as_ref()],
+        bump = pool.principal_vault_bump,
+    )]
+    pub principal_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"token_bag", pool.key().as_ref()],
+        bump = pool.rewards_vault_bump,
+    )]
+    pub rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"bonus_reserve", pool.key().as_ref()],
+        bump = pool.bonus_reserve_bump,
+    )]
+    pub bonus_reserve_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
 // Check eligibility context - updated to match stake record PDA pattern
 #[derive(Accounts)]
 #[instruction(stake_index: u32)]
 pub struct CheckEligibility<'info> {
+    // Pool this instruction operates on
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
     #[account(
-        seeds = [b"stake", staker.key().as_ref(), stake_index.to_le_bytes().as_ref()],
+        seeds = [b"stake", pool.key().as_ref(), staker.key().as_ref(), stake_index.to_le_bytes().as_ref()],
         bump,
     )]
     pub stake_record: Account<'info, StakeRecord>,
 
+    #[account(
+        seeds = [b"distribution_state", pool.key().as_ref()],
+        bump,
+    )]
+    pub distribution_state: Account<'info, DistributionState>,
+
+    #[account(
+        seeds = [b"staking_config", pool.key().as_ref()],
+        bump,
+    )]
+    pub staking_config: Account<'info, StakingConfig>,
+
     /// CHECK: The staker's public key for PDA derivation
     pub staker: AccountInfo<'info>,
 }
 
+// Which pool vault a VaultCreated event refers to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum VaultKind {
+    Principal,
+    Rewards,
+    BonusReserve,
+    KeeperIncentive,
+    BuybackTreasury,
+}
+
+// Events - all amounts are raw token units, timestamps are unix seconds
+
+#[event]
+pub struct PoolInitialized {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,          // Decimals for formatting every raw amount emitted by this pool
+    pub min_stake_amount: u64,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub pool: Pubkey,
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultCreated {
+    pub pool: Pubkey,
+    pub vault: Pubkey,
+    pub kind: VaultKind,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RolesUpdated {
+    pub pool: Pubkey,
+    pub distributor: Pubkey,
+    pub treasurer: Pubkey,
+    pub pauser: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseFlagsUpdated {
+    pub pool: Pubkey,
+    pub paused: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockTierChanged {
+    pub pool: Pubkey,
+    pub lock_duration: i64,
+    pub monthly_weight_bps: u64,
+    pub completion_multiplier_bps: u64,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommissionWalletsUpdated {
+    pub pool: Pubkey,
+    pub fee_wallet: Pubkey,
+    pub expense_wallet: Pubkey,
+    pub marketing_wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeCreated {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub gross_amount: u64,
+    pub commission_amount: u64,
+    pub net_amount: u64,
+    pub lock_duration: i64,
+    pub unlock_time: i64,
+    pub monthly_weight_bps: u64,
+    pub completion_multiplier_bps: u64,
+    pub round: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeToppedUp {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub gross_amount: u64,
+    pub commission_amount: u64,
+    pub net_amount: u64,
+    pub new_total_amount: u64,
+    pub restarted_lock: bool,
+    pub lock_duration: i64,
+    pub unlock_time: i64,
+    pub round: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsAssigned {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub amount: u64,
+    pub round: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub amount: u64,
+    pub round: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Unstaked {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub principal: u64,
+    pub bonus: u64,
+    pub rewards: u64,
+    pub early: bool,
+    pub round: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawn {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub principal: u64,
+    pub forfeited_rewards: u64,
+    pub round: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyBuybackExecuted {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub principal: u64,
+    pub payout: u64,
+    pub discount_bps: u64,
+    pub forfeited_rewards: u64,
+    pub round: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BuybackDiscountUpdated {
+    pub pool: Pubkey,
+    pub buyback_discount_bps: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UserTokensBurned {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub user_total_burned: u64,
+    pub protocol_total_burned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsCranked {
+    pub pool: Pubkey,
+    pub keeper: Pubkey,
+    pub round: u64,
+    pub stakes_credited: u64,
+    pub total_rewards: u64,
+    pub keeper_tip: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundIntervalUpdated {
+    pub pool: Pubkey,
+    pub round_interval: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeeperTipUpdated {
+    pub pool: Pubkey,
+    pub keeper_tip_per_stake: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardRootPublished {
+    pub pool: Pubkey,
+    pub round: u64,
+    pub root: [u8; 32],
+    pub total_amount: u64,
+    pub leaf_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MerkleRewardClaimed {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub round: u64,
+    pub leaf_index: u32,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundLeftoverRolled {
+    pub pool: Pubkey,
+    pub round: u64,
+    pub amount: u64,
+    pub destination: Pubkey,     // Carryover sink, or default when carried into the next round
+    pub reward_carryover: u64,   // Pending carryover after this leftover or forfeit
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CarryoverSinkUpdated {
+    pub pool: Pubkey,
+    pub carryover_sink: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommissionRouted {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub stake_index: u32,
+    pub fee_amount: u64,
+    pub expense_amount: u64,
+    pub marketing_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesBurned {
+    pub pool: Pubkey,
+    pub fee_wallet: Pubkey,
+    pub amount: u64,
+    pub total_commission_burned: u64,
+    pub round: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsFunded {
+    pub pool: Pubkey,
+    pub round: u64,          // Round the deposit is recorded as
+    pub funder: Pubkey,
+    pub amount: u64,         // Amount that arrived in the rewards vault
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundRecorded {
+    pub pool: Pubkey,
+    pub round: u64,
+    pub total_rr3_for_rewards: u64,
+    pub reward_amount: u64,   // Includes carryover_amount
+    pub carryover_amount: u64,
+    pub total_weighted_staked: u64,
+    pub acc_reward_per_weighted_share: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundCompleted {
+    pub pool: Pubkey,
+    pub round: u64,
+    pub allocated_amount: u64,
+    pub amount_assigned: u64,
+    pub stakes_assigned: u32,
+    pub undistributed_dust: u64,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum StakeError {
     #[msg("No rewards available to claim")]
     NoRewardsAvailable,
     #[msg("Insufficient funds in treasury to pay rewards")]
     InsufficientTreasuryFunds,
-    #[msg("Too early for distribution - must wait at least 5 minutes between distributions")]
+    #[msg("Too early for distribution - the round interval has not elapsed since the last round")]
     TooEarlyForDistribution,
     #[msg("No monthly collection recorded for distribution")]
     NoMonthlyCollection,
@@ -1243,19 +4332,232 @@ pub enum StakeError {
     AlreadyReceivedRewardsThisRound,
     #[msg("Professional staking periods ensure eligibility - all stakers are eligible")]
     IneligibleForRewards,
-    #[msg("No expense fees available to transfer")]
-    NoExpenseFeesToTransfer,
-    #[msg("No marketing fees available to transfer")]
-    NoMarketingFeesToTransfer,
-    #[msg("No fee wallet fees available to transfer")]
-    NoFeeWalletFeesToTransfer,
-    #[msg("No burn fees available to transfer")]
-    NoBurnFeesToTransfer,
 
     #[msg("Invalid stake index - must match expected next stake number")]
     InvalidStakeIndex,
-    #[msg("Invalid lock period - must be 3 months, 6 months, 1 year, 2 years, or 3 years")]
+    #[msg("Invalid lock period - must match an enabled lock tier in the staking config")]
     InvalidLockPeriod,
     #[msg("Tokens are still locked - cannot claim rewards until unlock time")]
     StillLocked,
+    #[msg("On-chain total weighted stake does not match the expected total")]
+    TotalStakedMismatch,
+    #[msg("Invalid lock tier - duration and weight must be positive, completion multiplier at least 1.0x")]
+    InvalidLockTier,
+    #[msg("A lock tier with this duration already exists")]
+    LockTierAlreadyExists,
+    #[msg("No lock tier with this duration")]
+    LockTierNotFound,
+    #[msg("Lock tier table is full")]
+    TooManyLockTiers,
+    #[msg("Bonus reserve cannot cover the completion bonus liability of this stake")]
+    InsufficientBonusReserve,
+    #[msg("Top-up amount is too small to add anything after commission")]
+    InvalidTopUpAmount,
+    #[msg("Stake amount is too small to stake anything after commission")]
+    InvalidStakeAmount,
+    #[msg("Stake is already unlocked - top it up with RestartLock")]
+    StakeAlreadyUnlocked,
+    #[msg("RestartLock cannot unlock the stake earlier than its current unlock time")]
+    LockShortened,
+    #[msg("Stake is already unlocked - unstake it instead of selling it to the buyback treasury")]
+    BuybackOfUnlockedStake,
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+    #[msg("This operation is paused")]
+    Paused,
+    #[msg("Unknown pause flag bits")]
+    InvalidPauseFlags,
+    #[msg("Buyback discount cannot exceed 100%")]
+    InvalidBuybackDiscount,
+    #[msg("Burn amount must be greater than zero")]
+    InvalidBurnAmount,
+    #[msg("Funding amount must be greater than zero")]
+    InvalidFundingAmount,
+    #[msg("Round interval is below the minimum allowed by this build")]
+    InvalidRoundInterval,
+    #[msg("Only the program upgrade authority can create pools")]
+    NotUpgradeAuthority,
+    #[msg("Stake amount is below the pool's minimum stake")]
+    BelowMinimumStake,
+    #[msg("Minimum stake does not fit in raw units with the mint's decimals")]
+    InvalidMinimumStake,
+    #[msg("Too many stake records for one crank")]
+    TooManyCrankStakes,
+    #[msg("Account is not a stake record of this pool")]
+    InvalidStakeAccount,
+    #[msg("Round must be the next distribution round")]
+    InvalidRound,
+    #[msg("Merkle leaf count must be between 1 and MAX_MERKLE_LEAVES")]
+    InvalidLeafCount,
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Reward already claimed for this round")]
+    RewardAlreadyClaimed,
+    #[msg("Round is already closed")]
+    RoundAlreadyClosed,
+    #[msg("The configured carryover sink token account must be provided")]
+    CarryoverSinkRequired,
+    #[msg("Principal vault holds less than the principal of all active stakes")]
+    PrincipalVaultUnderfunded,
+    #[msg("Merkle rounds are paid through claim_with_proof")]
+    MerkleRoundNotAssignable,
+    #[msg("The reward root of a Merkle round must be provided")]
+    RewardRootRequired,
+    #[msg("The previous round must be closed before the next one opens")]
+    PreviousRoundOpen,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        keccak::hashv(&[&[1u8], left, right]).0
+    }
+
+    // Three leaves padded with a zero leaf: root = node(node(l0, l1), node(l2, 0))
+    fn three_leaf_tree() -> ([[u8; 32]; 3], [u8; 32]) {
+        let user = Pubkey::new_unique();
+        let leaves = [
+            merkle_leaf(0, &user, 0, 100),
+            merkle_leaf(1, &user, 1, 200),
+            merkle_leaf(2, &user, 2, 300),
+        ];
+        let root = merkle_node(&merkle_node(&leaves[0], &leaves[1]), &merkle_node(&leaves[2], &[0u8; 32]));
+        (leaves, root)
+    }
+
+    #[test]
+    fn merkle_proof_accepts_a_valid_proof() {
+        let (leaves, root) = three_leaf_tree();
+        let proof = [[0u8; 32], merkle_node(&leaves[0], &leaves[1])];
+        assert!(verify_merkle_proof(&proof, root, leaves[2], 2, 3));
+
+        let proof = [leaves[0], merkle_node(&leaves[2], &[0u8; 32])];
+        assert!(verify_merkle_proof(&proof, root, leaves[1], 1, 3));
+    }
+
+    #[test]
+    fn merkle_proof_rejects_the_wrong_index() {
+        let (leaves, root) = three_leaf_tree();
+        let proof = [leaves[0], merkle_node(&leaves[2], &[0u8; 32])];
+        assert!(!verify_merkle_proof(&proof, root, leaves[1], 0, 3));
+        assert!(!verify_merkle_proof(&proof, root, leaves[1], 5, 3));
+
+        // The index is part of the leaf, so a leaf can't be rebuilt for another position
+        let user = Pubkey::new_unique();
+        assert_ne!(merkle_leaf(0, &user, 0, 100), merkle_leaf(1, &user, 0, 100));
+    }
+
+    #[test]
+    fn merkle_proof_rejects_a_short_proof() {
+        // With odd-node promotion leaf 2 would be proven by one sibling: root = node(node(l0, l1), l2)
+        let (leaves, _) = three_leaf_tree();
+        let promoted_root = merkle_node(&merkle_node(&leaves[0], &leaves[1]), &leaves[2]);
+        let proof = [merkle_node(&leaves[0], &leaves[1])];
+        assert!(!verify_merkle_proof(&proof, promoted_root, leaves[2], 1, 3));
+        assert!(!verify_merkle_proof(&[], leaves[2], leaves[2], 2, 3));
+        assert_eq!(merkle_depth(1), 0);
+        assert_eq!(merkle_depth(3), 2);
+        assert_eq!(merkle_depth(MAX_MERKLE_LEAVES), 16);
+    }
+
+    #[test]
+    fn format_ui_amount_without_decimals() {
+        assert_eq!(format_ui_amount(0, 0), "0");
+        assert_eq!(format_ui_amount(1_234, 0), "1234");
+    }
+
+    #[test]
+    fn format_ui_amount_trims_trailing_zeros() {
+        assert_eq!(format_ui_amount(1_500_000_000, 9), "1.5");
+        assert_eq!(format_ui_amount(2_000_000_000, 9), "2");
+        assert_eq!(format_ui_amount(1_000_001, 6), "1.000001");
+        assert_eq!(format_ui_amount(50, 6), "0.00005");
+        assert_eq!(format_bps_multiplier(10_100), "1.01");
+    }
+
+    #[test]
+    fn format_ui_amount_handles_the_maximum_value() {
+        assert_eq!(format_ui_amount(u64::MAX, 0), "18446744073709551615");
+        assert_eq!(format_ui_amount(u64::MAX, 9), "18446744073.709551615");
+        assert_eq!(format_ui_amount(u64::MAX, 20), "0.18446744073709551615");
+        assert_eq!(format_ui_amount(u64::MAX, u8::MAX), format!("0.{}18446744073709551615", "0".repeat(235)));
+    }
+
+    #[test]
+    fn whole_tokens_to_raw_rejects_overflow() {
+        assert_eq!(whole_tokens_to_raw(100, 9).unwrap(), 100_000_000_000);
+        assert_eq!(whole_tokens_to_raw(1, 19).unwrap(), 10_000_000_000_000_000_000);
+        assert_eq!(whole_tokens_to_raw(0, u8::MAX).unwrap(), 0);
+        assert_eq!(whole_tokens_to_raw(1, 20).unwrap_err(), error!(StakeError::InvalidMinimumStake));
+        assert_eq!(whole_tokens_to_raw(u64::MAX, 9).unwrap_err(), error!(StakeError::InvalidMinimumStake));
+    }
+
+    #[test]
+    fn weighted_top_up_averages_stake_time_of_a_locked_stake() {
+        // 100 tokens staked at t=0 for 1000s, 100 more added at t=500
+        let (stake_time, unlock_time) = weighted_top_up_times(0, 1_000, 1_000, 100, 100, 500).unwrap();
+        assert_eq!(stake_time, 250);
+        assert_eq!(unlock_time, 1_250);
+    }
+
+    #[test]
+    fn weighted_top_up_rejects_a_matured_stake() {
+        // Matured long ago: the average would still unlock in the past
+        let result = weighted_top_up_times(0, 1_000, 1_000, 1_000, 1, 1_000_000);
+        assert_eq!(result.unwrap_err(), error!(StakeError::StakeAlreadyUnlocked));
+
+        // Matured exactly now
+        let result = weighted_top_up_times(0, 1_000, 1_000, 100, 100, 1_000);
+        assert_eq!(result.unwrap_err(), error!(StakeError::StakeAlreadyUnlocked));
+    }
+
+    #[test]
+    fn reward_round_books_only_its_own_share() {
+        let mut reward_round = RewardRound {
+            round: 0,
+            allocated_amount: 0,
+            total_weighted_staked: 0,
+            acc_reward_increment: 0,
+            amount_assigned: 0,
+            stakes_assigned: 0,
+            opened_at: 0,
+            closed_at: 0,
+            undistributed_dust: 0,
+            merkle: false,
+        };
+        // 900 rewards over 3_000 weighted: 0.3 per weighted unit
+        let acc_increment = 900 * ACC_REWARD_PRECISION / 3_000;
+        reward_round.open(2, 900, 3_000, acc_increment, false, 0);
+
+        // A stake that never settled round 1 settles both rounds now, but round 2 only books its own share
+        assert_eq!(accrued_rewards(1_000, acc_increment * 2), 600);
+        assert_eq!(reward_round.round_share(1_000), 300);
+
+        reward_round.record_assignment(reward_round.round_share(1_000));
+        reward_round.record_assignment(reward_round.round_share(2_000));
+        assert_eq!(reward_round.amount_assigned, 900);
+        assert_eq!(reward_round.stakes_assigned, 2);
+    }
+
+    #[test]
+    fn restart_lock_rejects_a_shorter_lock() {
+        // One month into a 3-year stake, restarting with 3 months would unlock years early
+        let now = 30 * 86_400;
+        let result = restart_unlock_time(LOCK_3_YEARS, LOCK_3_MONTHS, now);
+        assert_eq!(result.unwrap_err(), error!(StakeError::LockShortened));
+
+        // Restarting with the same tier extends the lock
+        assert_eq!(restart_unlock_time(LOCK_3_YEARS, LOCK_3_YEARS, now).unwrap(), now + LOCK_3_YEARS);
+
+        // A matured stake can restart with any tier
+        assert_eq!(restart_unlock_time(LOCK_3_MONTHS, LOCK_3_MONTHS, LOCK_1_YEAR).unwrap(), LOCK_1_YEAR + LOCK_3_MONTHS);
+    }
+
+    #[test]
+    fn staking_commission_does_not_overflow() {
+        assert_eq!(staking_commission(10_000), 333);
+        assert_eq!(staking_commission(u64::MAX), ((u64::MAX as u128) * 333 / 10_000) as u64);
+    }
 }